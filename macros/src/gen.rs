@@ -0,0 +1,1319 @@
+use crate::limbs::generate_limbs;
+use crate::literal::{low_mask_literal, used_mask_literal};
+use crate::math::{exact_decimal, storage, Storage};
+use crate::parse::{storage_of, Input, Inputs, Layout, LiteralInput, Overflow, Rounding};
+use crate::types::{
+    core_primitive_type, signed_int_qualified, signed_wide_int_qualified, unsigned_int_qualified,
+    unsigned_wide_int_qualified,
+};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, Ident, Type, Visibility};
+
+struct Data {
+    attrs: Vec<Attribute>,
+    visibility: Visibility,
+    name: Ident,
+    total_bits: u8,
+    used_bits: u8,
+    int_bits: u8,
+    frac_bits: u8,
+    pad_bits: u8,
+    inner_type: Type,
+    /// Twice as wide as `inner_type`, or `None` for 128-bit inner types.
+    wide_type: Option<Type>,
+    denominator: f64,
+    conversion_factor: f64,
+    signed: bool,
+    q_notation: String,
+    used_mask: Literal,
+    min_float: f64,
+    max_float: f64,
+    min_ulps: f64,
+    end_ulps: f64,
+    overflow: Overflow,
+    rounding: Rounding,
+    layout: Layout,
+}
+
+/// The inner type is the narrowest one that holds the used bits, unless the
+/// `storage` option overrides it (already validated against the format).
+pub fn generate(input: Input) -> syn::Result<TokenStream> {
+    match storage_of(input.format(), &input.options) {
+        Storage::Primitive(total_bits) => generate_from_data(prepare_data(input, total_bits)?),
+        Storage::Limbs(limbs) => generate_limbs(input, limbs),
+    }
+}
+
+/// Generates every type in `inputs`, plus a `From` impl between each pair of
+/// them where the conversion is lossless.
+pub fn generate_all(inputs: Inputs) -> syn::Result<TokenStream> {
+    let conversions = generate_lossless_conversions(&inputs.0);
+    let types = inputs
+        .0
+        .into_iter()
+        .map(generate)
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(quote! {
+        #(#types)*
+        #conversions
+    })
+}
+
+/// A `cfg` on either type also applies to the conversion between them. Types
+/// stored in limbs do not implement `QNum`, so they are skipped.
+fn generate_lossless_conversions(inputs: &[Input]) -> TokenStream {
+    let cfgs = |input: &Input| -> Vec<Attribute> {
+        input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .cloned()
+            .collect()
+    };
+    let mut conversions = TokenStream::new();
+    for from in inputs {
+        for to in inputs {
+            if from.name == to.name || !from.format().is_lossless_into(to.format()) {
+                continue;
+            }
+            let is_limbs =
+                |input: &Input| matches!(storage(input.format().used_bits()), Storage::Limbs(_));
+            if is_limbs(from) || is_limbs(to) {
+                continue;
+            }
+            let (from_name, to_name) = (&from.name, &to.name);
+            let (from_cfgs, to_cfgs) = (cfgs(from), cfgs(to));
+            conversions.extend(quote! {
+                #(#from_cfgs)*
+                #(#to_cfgs)*
+                impl ::core::convert::From<#from_name> for #to_name {
+                    fn from(value: #from_name) -> Self {
+                        ::q_num::QNum::convert(value)
+                    }
+                }
+            });
+        }
+    }
+    conversions
+}
+
+/// Generates a `const` evaluation of the number, so that a number that is out
+/// of range or not a number is a compile error.
+///
+/// A proc macro only sees the tokens of the type, not the format it was defined
+/// with, so the bits cannot be computed here. Instead, `from_str_const` parses
+/// and range-checks the number during constant evaluation. An inline `const`
+/// block (rather than a nested `const` item) keeps `q!(Self, ..)` working
+/// inside an `impl`.
+pub fn generate_literal(input: LiteralInput) -> TokenStream {
+    let LiteralInput { ty, value } = input;
+    let type_name = quote!(#ty).to_string().replace(' ', "");
+    let out_of_range = format!("`{value}` is out of range for `{type_name}`");
+    let invalid = format!("`{value}` is not a number");
+    quote! {
+        const {
+            match <#ty>::from_str_const(#value) {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(::q_num::Error::Overflow) => ::core::panic!(#out_of_range),
+                ::core::result::Result::Err(_) => ::core::panic!(#invalid),
+            }
+        }
+    }
+}
+
+#[rustfmt::skip]
+fn prepare_data(input: Input, total_bits: u8) -> syn::Result<Data> {
+    assert!(input.int_bits >= 1);
+    let int_bits = input.int_bits;
+    let frac_bits = input.frac_bits;
+    let used_bits = input.int_bits + input.frac_bits;
+    let pad_bits = total_bits - used_bits;
+    let denominator = 2f64.powi(frac_bits as i32);
+    let signed = input.signed;
+    let layout = input.options.layout;
+    let (min_float, max_float) = if signed {
+        let x = 2f64.powi(int_bits as i32 - 1);
+        (-x, x - 1.0 / denominator)
+    } else {
+        (0.0, 2f64.powi(int_bits as i32) - 1.0 / denominator)
+    };
+    // The range of `value * DENOMINATOR` (inclusive start, exclusive end).
+    let (min_ulps, end_ulps) = if signed {
+        let x = 2f64.powi(used_bits as i32 - 1);
+        (-x, x)
+    } else {
+        (0.0, 2f64.powi(used_bits as i32))
+    };
+    Ok(Data {
+        attrs: input.attrs,
+        visibility: input.visibility,
+        name: input.name,
+        total_bits, used_bits, int_bits, frac_bits, pad_bits,
+        inner_type: if input.signed {
+            signed_int_qualified(total_bits)?
+        } else {
+            unsigned_int_qualified(total_bits)?
+        },
+        wide_type: if total_bits == 128 {
+            None
+        } else if input.signed {
+            Some(signed_wide_int_qualified(total_bits)?)
+        } else {
+            Some(unsigned_wide_int_qualified(total_bits)?)
+        },
+        denominator,
+        conversion_factor: match layout {
+            Layout::Left => 2f64.powi((frac_bits + pad_bits) as i32),
+            Layout::Right => denominator,
+        },
+        signed,
+        q_notation: if signed {
+            format!("Q{int_bits}.{frac_bits}")
+        } else {
+            format!("UQ{int_bits}.{frac_bits}")
+        },
+        min_float, max_float, min_ulps, end_ulps,
+        used_mask: match layout {
+            Layout::Left => used_mask_literal(total_bits, pad_bits),
+            Layout::Right => low_mask_literal(used_bits),
+        },
+        overflow: input.options.overflow,
+        rounding: input.options.rounding,
+        layout,
+    })
+}
+
+fn generate_from_data(data: Data) -> syn::Result<TokenStream> {
+    #[rustfmt::skip]
+    let Data {
+        attrs, visibility: vis, name, total_bits, used_bits, int_bits, frac_bits, pad_bits,
+        inner_type, wide_type, denominator, conversion_factor, signed,
+        q_notation, used_mask, min_float, max_float, min_ulps, end_ulps,
+        overflow, rounding, layout
+    } = data;
+    let bool = core_primitive_type("bool")?;
+    let u8 = core_primitive_type("u8")?;
+    let f64 = core_primitive_type("f64")?;
+    let f32 = core_primitive_type("f32")?;
+    let u128 = core_primitive_type("u128")?;
+    let to_parts = if signed {
+        quote! {
+            let n = self.to_right_bits();
+            (n < 0, n.unsigned_abs() as #u128)
+        }
+    } else {
+        quote! { (false, self.to_right_bits() as #u128) }
+    };
+    // Every operation works on the left-aligned bits, where the padding is
+    // zeroed; for the right layout, these are shifted in and out of place.
+    let (to_left_bits, from_left, from_bits, layout_doc) = match layout {
+        Layout::Left => (
+            quote! { self.0 },
+            quote! { Self(bits) },
+            quote! { Self::from_left_bits(bits) },
+            "zeroed out",
+        ),
+        Layout::Right => (
+            quote! { self.0 << Self::PAD_BITS },
+            quote! { Self(bits >> Self::PAD_BITS) },
+            quote! { Self::from_right_bits(bits) },
+            "sign-extended (or zeroed out for unsigned types)",
+        ),
+    };
+    let from_bits_doc = format!(" Note: ensures unused bits (the padding) are {layout_doc}.");
+    let right_aligned = layout == Layout::Right;
+    // The largest magnitudes of negative and non-negative values.
+    let (neg_limit, pos_limit) = if signed {
+        let x = 1u128 << (used_bits - 1);
+        (x, x - 1)
+    } else {
+        (0, u128::MAX >> (128 - used_bits as u32))
+    };
+    // A `u128` at or above 2^128 - 2^103 casts to an infinite `f32`, so those
+    // are halved first. Keeping the lowest bit leaves the rounding unaffected.
+    let to_f32 = if !signed && total_bits == 128 {
+        quote! {
+            if self.0 >> 127 == 0 {
+                (self.0 as #f32) / (Self::CONVERSION_FACTOR as #f32)
+            } else {
+                ((self.0 >> 1 | self.0 & 1) as #f32) / ((Self::CONVERSION_FACTOR / 2.0) as #f32)
+            }
+        }
+    } else {
+        quote! { (self.0 as #f32) / (Self::CONVERSION_FACTOR as #f32) }
+    };
+    // Only offer an `f32` conversion via `From` when it is lossless.
+    let from_for_f32 = if used_bits <= 24 {
+        quote! {
+            impl ::core::convert::From<#name> for #f32 {
+                fn from(value: #name) -> Self {
+                    value.to_f32()
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let from_f64_round = generate_from_f64_round(
+        &vis,
+        min_ulps,
+        end_ulps,
+        quote! { Self::from_right_bits(n as #inner_type) },
+    )?;
+    let try_from_float = generate_try_from_float(&name)?;
+    let modulus = 2f64.powi(int_bits as i32);
+    let int_methods = generate_int_methods(&vis, &inner_type, signed, int_bits == total_bits);
+    let int_conversions = generate_int_conversions(&name, &inner_type, int_bits, signed)?;
+    let overflow_methods = generate_overflow_methods(&vis, &inner_type, wide_type.as_ref(), signed);
+    let ops = generate_ops(&name, signed, overflow)?;
+    let rounding = rounding_mode(rounding);
+    let doc = generate_doc(signed, int_bits, frac_bits, &q_notation, &inner_type);
+    let doc_separator = doc_separator(&attrs);
+    let items = quote! {
+        impl #name {
+            #vis const Q_NOTATION: &'static str = #q_notation;
+            #vis const SIGNED: #bool = #signed;
+            #vis const TOTAL_BITS: #u8 = #total_bits;
+            #vis const USED_BITS: #u8 = #used_bits;
+            #vis const INT_BITS: #u8 = #int_bits;
+            #vis const FRAC_BITS: #u8 = #frac_bits;
+            #vis const PAD_BITS: #u8 = #pad_bits;
+            #vis const USED_MASK: #inner_type = #used_mask;
+            #vis const MIN_FLOAT: #f64 = #min_float;
+            #vis const MAX_FLOAT: #f64 = #max_float;
+            #vis const MIN: Self = Self::from_left_bits(<#inner_type>::MIN);
+            #vis const MAX: Self = Self::from_left_bits(<#inner_type>::MAX);
+            #vis const DENOMINATOR: #f64 = #denominator;
+            #vis const CONVERSION_FACTOR: #f64 = #conversion_factor;
+
+            /// The rounding mode used by `TryFrom<f64>`.
+            #vis const ROUNDING: ::q_num::RoundingMode = ::q_num::RoundingMode::#rounding;
+
+            /// Returns the inner value.
+            #vis const fn to_bits(self) -> #inner_type { self.0 }
+
+            /// Builds a new instance using the provided bits;
+            ///
+            #[doc = #from_bits_doc]
+            #vis const fn from_bits(bits: #inner_type) -> Self {
+                #from_bits
+            }
+
+            /// Returns the bits left-aligned, i.e. with the padding below the
+            /// value and zeroed out.
+            #vis const fn to_left_bits(self) -> #inner_type {
+                #to_left_bits
+            }
+
+            /// Builds a new instance from left-aligned bits, ignoring the
+            /// padding.
+            #vis const fn from_left_bits(bits: #inner_type) -> Self {
+                Self::from_left(bits & (!(0 as #inner_type) << Self::PAD_BITS))
+            }
+
+            /// Returns the bits right-aligned, i.e. with the padding above the
+            /// value and sign-extended (or zeroed out for unsigned types).
+            #vis const fn to_right_bits(self) -> #inner_type {
+                self.to_left_bits() >> Self::PAD_BITS
+            }
+
+            /// Builds a new instance from right-aligned bits, ignoring the
+            /// padding.
+            #vis const fn from_right_bits(bits: #inner_type) -> Self {
+                Self::from_left(bits << Self::PAD_BITS)
+            }
+
+            /// Builds a new instance from left-aligned bits whose padding is
+            /// already zeroed out.
+            const fn from_left(bits: #inner_type) -> Self {
+                #from_left
+            }
+
+            /// Parses `s` like `FromStr`, but in a `const` context. This is what
+            /// the `q!` macro uses.
+            #vis const fn from_str_const(
+                s: &::core::primitive::str,
+            ) -> ::core::result::Result<Self, ::q_num::Error> {
+                match ::q_num::__private::parse(s.as_bytes(), Self::FRAC_BITS, Self::ROUNDING) {
+                    ::core::result::Result::Ok((negative, magnitude)) => {
+                        match Self::checked_from_parts(negative, magnitude) {
+                            ::core::option::Option::Some(value) => ::core::result::Result::Ok(value),
+                            ::core::option::Option::None => {
+                                ::core::result::Result::Err(::q_num::Error::Overflow)
+                            }
+                        }
+                    }
+                    ::core::result::Result::Err(error) => ::core::result::Result::Err(error),
+                }
+            }
+
+            /// Builds a value from a sign and a magnitude in units of
+            /// 2^-`FRAC_BITS`, or returns `None` if it is out of range.
+            const fn checked_from_parts(
+                negative: #bool,
+                magnitude: #u128,
+            ) -> ::core::option::Option<Self> {
+                let limit = if negative { #neg_limit } else { #pos_limit };
+                if magnitude > limit {
+                    return ::core::option::Option::None;
+                }
+                ::core::option::Option::Some(Self::wrapping_from_parts(negative, magnitude))
+            }
+
+            /// Like `checked_from_parts`, but wraps around at the numeric bounds.
+            const fn wrapping_from_parts(negative: #bool, magnitude: #u128) -> Self {
+                // The cast and the shift drop the high bits.
+                let bits = (magnitude as #inner_type) << Self::PAD_BITS;
+                Self::from_left(if negative { bits.wrapping_neg() } else { bits })
+            }
+
+            #from_f64_round
+
+            /// Converts `value` using the `ROUNDING` mode, saturating at the
+            /// numeric bounds if it is out of range.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `value` is NaN.
+            #vis fn from_f64_saturating(value: #f64) -> Self {
+                ::core::assert!(!value.is_nan(), "NaN cannot be converted to {}", Self::Q_NOTATION);
+                Self::from_f64_lossy(value)
+            }
+
+            /// Converts `value` using the `ROUNDING` mode, wrapping around at the
+            /// numeric bounds if it is out of range.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `value` is NaN or infinite.
+            #vis fn from_f64_wrapping(value: #f64) -> Self {
+                ::core::assert!(
+                    value.is_finite(),
+                    "{} cannot be converted to {}",
+                    value,
+                    Self::Q_NOTATION
+                );
+                // Reducing first keeps `value * DENOMINATOR` finite. The `%`
+                // result is exact and keeps the sign, so the rounding is not
+                // affected. The negation, the shift and the final cast wrap.
+                let n = Self::ROUNDING.round((value % #modulus) * Self::DENOMINATOR);
+                let bits = if n < 0.0 {
+                    ((-n) as #u128).wrapping_neg()
+                } else {
+                    n as #u128
+                };
+                Self::from_left((bits << Self::PAD_BITS) as #inner_type)
+            }
+
+            /// Converts `value` using the `ROUNDING` mode, without failing or
+            /// panicking: out-of-range values and the infinities saturate at the
+            /// numeric bounds, and NaN converts to zero.
+            #vis fn from_f64_lossy(value: #f64) -> Self {
+                let n = Self::ROUNDING.round(value * Self::DENOMINATOR);
+                if n.is_nan() {
+                    Self(0)
+                } else if n < #min_ulps {
+                    Self::MIN
+                } else if n >= #end_ulps {
+                    Self::MAX
+                } else {
+                    Self::from_right_bits(n as #inner_type)
+                }
+            }
+
+            /// Converts to the nearest `f32`.
+            ///
+            /// This is lossless if `USED_BITS <= 24`; otherwise, precision may be
+            /// lost, since an `f32` has a 24-bit significand.
+            #vis fn to_f32(self) -> #f32 {
+                #to_f32
+            }
+
+            #int_methods
+
+            #overflow_methods
+        }
+
+        impl ::core::str::FromStr for #name {
+            type Err = ::q_num::Error;
+
+            /// Parses a decimal, binary (`0b`), octal (`0o`) or hexadecimal
+            /// (`0x`) number exactly, rounding with the `ROUNDING` mode.
+            fn from_str(s: &::core::primitive::str) -> ::core::result::Result<Self, Self::Err> {
+                ::q_num::__private::from_str(s)
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            /// Formats the exact value in decimal, honoring the precision
+            /// (rounding with ties to even), width, fill and alignment.
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::q_num::__private::display(*self, f)
+            }
+        }
+
+        impl ::core::fmt::LowerExp for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::q_num::__private::lower_exp(*self, f)
+            }
+        }
+
+        impl ::core::fmt::Binary for #name {
+            /// Formats the inner value. The alternate form (`{:#b}`) shows the
+            /// layout instead, e.g. `1|011.01__` for a `Q4.2` (or `__1|011.01`
+            /// with `layout = right`).
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                if f.alternate() {
+                    ::q_num::__private::binary_layout(
+                        self.0 as #u128,
+                        Self::TOTAL_BITS,
+                        Self::INT_BITS,
+                        Self::FRAC_BITS,
+                        Self::SIGNED,
+                        #right_aligned,
+                        f,
+                    )
+                } else {
+                    ::core::fmt::Binary::fmt(&self.0, f)
+                }
+            }
+        }
+
+        impl ::core::fmt::Octal for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Octal::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::fmt::LowerHex for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::LowerHex::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::fmt::UpperHex for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::UpperHex::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::fmt::Debug for #name {
+            /// Formats the exact value and the format, e.g. `X(-1.25, Q6.2)`.
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::q_num::__private::debug(*self, ::core::stringify!(#name), f)
+            }
+        }
+
+        impl ::core::convert::From<#name> for #f64 {
+            fn from(value: #name) -> Self {
+                (value.0 as #f64) / #name::CONVERSION_FACTOR
+            }
+        }
+
+        #try_from_float
+
+        #from_for_f32
+
+        #int_conversions
+
+        impl ::q_num::QNum for #name {
+            const Q_NOTATION: &'static str = #name::Q_NOTATION;
+            const SIGNED: #bool = #name::SIGNED;
+            const INT_BITS: #u8 = #name::INT_BITS;
+            const FRAC_BITS: #u8 = #name::FRAC_BITS;
+            const MIN: Self = #name::MIN;
+            const MAX: Self = #name::MAX;
+            const ROUNDING: ::q_num::RoundingMode = #name::ROUNDING;
+
+            fn to_parts(self) -> (#bool, #u128) {
+                #to_parts
+            }
+
+            fn from_parts(negative: #bool, magnitude: #u128) -> ::core::option::Option<Self> {
+                #name::checked_from_parts(negative, magnitude)
+            }
+
+            fn from_parts_wrapping(negative: #bool, magnitude: #u128) -> Self {
+                #name::wrapping_from_parts(negative, magnitude)
+            }
+        }
+
+        #ops
+    };
+    let items = apply_cfgs(&attrs, items);
+    Ok(quote! {
+        #(#attrs)*
+        #doc_separator
+        #[doc = #doc]
+        #[derive(
+            ::core::marker::Copy,
+            ::core::clone::Clone,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::cmp::PartialOrd,
+            ::core::cmp::Ord,
+            ::core::hash::Hash,
+        )]
+        #vis struct #name(#inner_type);
+
+        #items
+    })
+}
+
+/// Returns the doc comment for the struct, describing its format, range and
+/// resolution.
+fn generate_doc(
+    signed: bool,
+    int_bits: u8,
+    frac_bits: u8,
+    q_notation: &str,
+    inner_type: &Type,
+) -> String {
+    let used_bits = int_bits + frac_bits;
+    let (kind, sign_note, min, max) = if signed {
+        let x = 1u128 << (used_bits - 1);
+        (
+            "A signed",
+            " (including the sign bit)",
+            exact_decimal(true, x, frac_bits),
+            exact_decimal(false, x - 1, frac_bits),
+        )
+    } else {
+        let max = u128::MAX >> (128 - used_bits as u32);
+        (
+            "An unsigned",
+            "",
+            exact_decimal(false, 0, frac_bits),
+            exact_decimal(false, max, frac_bits),
+        )
+    };
+    let resolution = exact_decimal(false, 1, frac_bits);
+    let inner_type = quote!(#inner_type).to_string().replace(' ', "");
+    let inner_type = inner_type.trim_start_matches("::core::primitive::");
+    format!(
+        " {kind} binary fixed-point number in `{q_notation}` format, with \
+         {int_bits} integer bits{sign_note} and {frac_bits} fractional bits, \
+         stored in `{inner_type}`.\n\n\
+         It ranges from {min} to {max} in steps of {resolution}."
+    )
+}
+
+/// Returns the `q_num::RoundingMode` variant for `rounding`.
+pub fn rounding_mode(rounding: Rounding) -> TokenStream {
+    match rounding {
+        Rounding::NearestTiesEven => quote! { NearestTiesEven },
+        Rounding::NearestTiesAway => quote! { NearestTiesAway },
+        Rounding::Floor => quote! { Floor },
+        Rounding::Ceil => quote! { Ceil },
+        Rounding::TowardZero => quote! { TowardZero },
+    }
+}
+
+/// Returns an empty doc line that separates the user's doc comment, if any,
+/// from the generated one.
+pub fn doc_separator(attrs: &[Attribute]) -> TokenStream {
+    if attrs.iter().any(|attr| attr.path().is_ident("doc")) {
+        quote! { #[doc = ""] }
+    } else {
+        quote! {}
+    }
+}
+
+/// A `cfg` on the struct must also apply to everything implemented for it, so
+/// `items` are wrapped in an anonymous `const` carrying the same `cfg`s.
+pub fn apply_cfgs(attrs: &[Attribute], items: TokenStream) -> TokenStream {
+    let cfgs: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect();
+    if cfgs.is_empty() {
+        items
+    } else {
+        quote! {
+            #(#cfgs)*
+            const _: () = { #items };
+        }
+    }
+}
+
+/// Generates `from_f64_round`, which rounds `value` to `n` units of the
+/// resolution, checks that `min_ulps <= n < end_ulps` and builds the value
+/// from `n` with `from_ulps`.
+pub fn generate_from_f64_round(
+    vis: &Visibility,
+    min_ulps: f64,
+    end_ulps: f64,
+    from_ulps: TokenStream,
+) -> syn::Result<TokenStream> {
+    let f64 = core_primitive_type("f64")?;
+    Ok(quote! {
+        /// Converts `value`, rounding it to the nearest multiple of the
+        /// resolution (`1 / DENOMINATOR`) in the direction given by `mode`.
+        ///
+        /// Returns `Error::NotANumber` if `value` is NaN, or
+        /// `Error::OutOfRange` if the rounded value is out of range.
+        #vis const fn from_f64_round(
+            value: #f64,
+            mode: ::q_num::RoundingMode,
+        ) -> ::core::result::Result<Self, ::q_num::Error> {
+            let n = mode.round(value * Self::DENOMINATOR);
+            if n.is_nan() {
+                return ::core::result::Result::Err(::q_num::Error::NotANumber);
+            }
+            // Not `contains`, which is not `const`.
+            if n < #min_ulps || n >= #end_ulps {
+                return ::core::result::Result::Err(::q_num::Error::OutOfRange {
+                    value,
+                    min: Self::MIN_FLOAT,
+                    max: Self::MAX_FLOAT,
+                });
+            }
+            ::core::result::Result::Ok(#from_ulps)
+        }
+    })
+}
+
+/// Generates `TryFrom<f64>` and `TryFrom<f32>`, both of which go through
+/// `from_f64_round`.
+pub fn generate_try_from_float(name: &Ident) -> syn::Result<TokenStream> {
+    let f64 = core_primitive_type("f64")?;
+    let f32 = core_primitive_type("f32")?;
+    Ok(quote! {
+        impl ::core::convert::TryFrom<#f64> for #name {
+            type Error = ::q_num::Error;
+
+            /// Converts `value` using the `ROUNDING` mode; see `from_f64_round`.
+            fn try_from(value: #f64) -> ::core::result::Result<Self, Self::Error> {
+                Self::from_f64_round(value, Self::ROUNDING)
+            }
+        }
+
+        impl ::core::convert::TryFrom<#f32> for #name {
+            type Error = ::q_num::Error;
+
+            /// Converts `value` using the `ROUNDING` mode; see `from_f64_round`.
+            fn try_from(value: #f32) -> ::core::result::Result<Self, Self::Error> {
+                Self::from_f64_round(value as #f64, Self::ROUNDING)
+            }
+        }
+    })
+}
+
+/// Generates the integer-part methods. These shift by `FRAC_BITS + PAD_BITS`
+/// rather than going through `f64`. If `no_frac` is true, there are no bits to
+/// shift out.
+fn generate_int_methods(
+    vis: &Visibility,
+    inner_type: &Type,
+    signed: bool,
+    no_frac: bool,
+) -> TokenStream {
+    let shift = quote! { (Self::FRAC_BITS + Self::PAD_BITS) };
+    let trunc = if signed && !no_frac {
+        quote! {
+            let floor = self.to_int_floor();
+            if self.0 < 0 && self.frac_part().0 != 0 {
+                floor + 1
+            } else {
+                floor
+            }
+        }
+    } else {
+        quote! { self.to_int_floor() }
+    };
+    // Rounds the floor up if the fractional part is above one half, or exactly
+    // one half of a non-negative value.
+    let round = if no_frac {
+        quote! { self.0 }
+    } else {
+        let up = if signed {
+            quote! { frac > half || (frac == half && bits >= 0) }
+        } else {
+            quote! { frac >= half }
+        };
+        quote! {
+            let bits = self.to_left_bits();
+            let floor = bits >> #shift;
+            let frac = bits & !(!(0 as #inner_type) << #shift);
+            let half = (1 as #inner_type) << (#shift - 1);
+            if #up { floor + 1 } else { floor }
+        }
+    };
+    let floor = if no_frac {
+        quote! { self.0 }
+    } else {
+        quote! { self.to_left_bits() >> #shift }
+    };
+    quote! {
+        /// Returns the integer part, rounded toward zero.
+        #vis fn to_int_trunc(self) -> #inner_type {
+            #trunc
+        }
+
+        /// Returns the integer part, rounded toward negative infinity.
+        #vis fn to_int_floor(self) -> #inner_type {
+            #floor
+        }
+
+        /// Returns the nearest integer, rounding ties away from zero.
+        #vis fn to_int_round(self) -> #inner_type {
+            #round
+        }
+
+        /// Returns the integer part (rounded toward negative infinity), with the
+        /// fractional bits cleared.
+        #vis fn int_part(self) -> Self {
+            Self::from_left(self.to_left_bits() & (!(0 as #inner_type) << #shift))
+        }
+
+        /// Returns the fractional part, i.e. `self - self.int_part()`. It is
+        /// never negative.
+        #vis fn frac_part(self) -> Self {
+            Self::from_left(self.to_left_bits() & !(!(0 as #inner_type) << #shift))
+        }
+    }
+}
+
+/// Generates conversions from the primitive integers, which place the integer
+/// into the integer bits. `From` is implemented when every value fits (which
+/// depends only on `INT_BITS`); otherwise `TryFrom` is implemented.
+fn generate_int_conversions(
+    name: &Ident,
+    inner_type: &Type,
+    int_bits: u8,
+    signed: bool,
+) -> syn::Result<TokenStream> {
+    let i128 = core_primitive_type("i128")?;
+    let u128 = core_primitive_type("u128")?;
+    // Signed sources are checked as an `i128` and unsigned sources as a `u128`,
+    // so the upper bound is also given clamped to an `i128`.
+    let (min_int, max_uint) = if signed {
+        let max = u128::MAX.checked_shr(129 - int_bits as u32).unwrap_or(0);
+        (i128::MIN >> (128 - int_bits as u32), max)
+    } else {
+        (0, u128::MAX >> (128 - int_bits as u32))
+    };
+    let max_int = max_uint.min(i128::MAX as u128) as i128;
+    let mut impls = TokenStream::new();
+    #[rustfmt::skip]
+    // The name, signedness and (except for `isize` and `usize`) width.
+    let sources: [(&str, bool, Option<u8>); 12] = [
+        ("i8", true, Some(8)), ("i16", true, Some(16)),
+        ("i32", true, Some(32)), ("i64", true, Some(64)),
+        ("i128", true, Some(128)), ("isize", true, None),
+        ("u8", false, Some(8)), ("u16", false, Some(16)),
+        ("u32", false, Some(32)), ("u64", false, Some(64)),
+        ("u128", false, Some(128)), ("usize", false, None),
+    ];
+    for (source, source_signed, bits) in sources {
+        let ty = core_primitive_type(source)?;
+        let in_range = if source_signed {
+            quote! { (#min_int..=#max_int).contains(&(value as #i128)) }
+        } else {
+            quote! { (value as #u128) <= #max_uint }
+        };
+        let lossless = match bits {
+            Some(bits) if source_signed => signed && bits <= int_bits,
+            Some(bits) if signed => bits < int_bits,
+            Some(bits) => bits <= int_bits,
+            None => false,
+        };
+        impls.extend(if lossless {
+            quote! {
+                impl ::core::convert::From<#ty> for #name {
+                    fn from(value: #ty) -> Self {
+                        Self::from_left((value as #inner_type) << (Self::FRAC_BITS + Self::PAD_BITS))
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl ::core::convert::TryFrom<#ty> for #name {
+                    type Error = ::q_num::Error;
+
+                    fn try_from(value: #ty) -> ::core::result::Result<Self, Self::Error> {
+                        if !(#in_range) {
+                            return ::core::result::Result::Err(::q_num::Error::Overflow);
+                        }
+                        ::core::result::Result::Ok(Self::from_left(
+                            (value as #inner_type) << (Self::FRAC_BITS + Self::PAD_BITS),
+                        ))
+                    }
+                }
+            }
+        });
+    }
+    Ok(impls)
+}
+
+/// Generates the arithmetic operators.
+///
+/// Because the left-aligned bits have the padding zeroed, addition,
+/// subtraction, negation and remainder work directly on them, and by default
+/// overflow behaves like it does for the inner primitive type.
+/// Multiplication and division are built on `overflowing_mul` and
+/// `overflowing_div`. The `overflow` option changes what `+`, `-`, `*`, `/` and
+/// unary `-` do on overflow.
+fn generate_ops(name: &Ident, signed: bool, overflow: Overflow) -> syn::Result<TokenStream> {
+    let u32 = core_primitive_type("u32")?;
+    let rhs = quote! { rhs };
+    let add = op_body(
+        overflow,
+        "add",
+        "attempt to add with overflow",
+        &rhs,
+        || {
+            quote! { Self::from_left(self.to_left_bits() + rhs.to_left_bits()) }
+        },
+    );
+    let sub = op_body(
+        overflow,
+        "sub",
+        "attempt to subtract with overflow",
+        &rhs,
+        || {
+            quote! { Self::from_left(self.to_left_bits() - rhs.to_left_bits()) }
+        },
+    );
+    let mul = op_body(
+        overflow,
+        "mul",
+        "attempt to multiply with overflow",
+        &rhs,
+        || {
+            quote! {
+                let (product, overflow) = self.overflowing_mul(rhs);
+                ::core::debug_assert!(!overflow, "attempt to multiply with overflow");
+                product
+            }
+        },
+    );
+    let div = op_body(
+        overflow,
+        "div",
+        "attempt to divide with overflow",
+        &rhs,
+        || {
+            quote! {
+                let (quotient, overflow) = self.overflowing_div(rhs);
+                ::core::debug_assert!(!overflow, "attempt to divide with overflow");
+                quotient
+            }
+        },
+    );
+    let neg = if signed {
+        let body = op_body(
+            overflow,
+            "neg",
+            "attempt to negate with overflow",
+            &quote! {},
+            || {
+                quote! { Self::from_left(-self.to_left_bits()) }
+            },
+        );
+        quote! {
+            impl ::core::ops::Neg for #name {
+                type Output = Self;
+
+                fn neg(self) -> Self::Output {
+                    #body
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    Ok(quote! {
+        impl ::core::ops::Add for #name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                #add
+            }
+        }
+
+        impl ::core::ops::Sub for #name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                #sub
+            }
+        }
+
+        impl ::core::ops::Mul for #name {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self::Output {
+                #mul
+            }
+        }
+
+        impl ::core::ops::Div for #name {
+            type Output = Self;
+
+            fn div(self, rhs: Self) -> Self::Output {
+                #div
+            }
+        }
+
+        impl ::core::ops::Rem for #name {
+            type Output = Self;
+
+            fn rem(self, rhs: Self) -> Self::Output {
+                Self::from_left(self.to_left_bits() % rhs.to_left_bits())
+            }
+        }
+
+        #neg
+
+        impl ::core::ops::Shl<#u32> for #name {
+            type Output = Self;
+
+            fn shl(self, rhs: #u32) -> Self::Output {
+                Self::from_left(self.to_left_bits() << rhs)
+            }
+        }
+
+        /// Shifts in the same way as the inner type (arithmetic shift for
+        /// signed types), then zeroes the padding.
+        impl ::core::ops::Shr<#u32> for #name {
+            type Output = Self;
+
+            fn shr(self, rhs: #u32) -> Self::Output {
+                Self::from_left_bits(self.to_left_bits() >> rhs)
+            }
+        }
+
+        impl ::core::ops::AddAssign for #name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ::core::ops::SubAssign for #name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl ::core::ops::MulAssign for #name {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl ::core::ops::DivAssign for #name {
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl ::core::ops::RemAssign for #name {
+            fn rem_assign(&mut self, rhs: Self) {
+                *self = *self % rhs;
+            }
+        }
+
+        impl ::core::ops::ShlAssign<#u32> for #name {
+            fn shl_assign(&mut self, rhs: #u32) {
+                *self = *self << rhs;
+            }
+        }
+
+        impl ::core::ops::ShrAssign<#u32> for #name {
+            fn shr_assign(&mut self, rhs: #u32) {
+                *self = *self >> rhs;
+            }
+        }
+    })
+}
+
+/// Generates the body of an operator according to the `overflow` option.
+/// `args` is `rhs` for binary operators and empty for unary ones; `default`
+/// generates the body used when no `overflow` option is given.
+pub fn op_body(
+    overflow: Overflow,
+    op: &str,
+    message: &str,
+    args: &TokenStream,
+    default: impl FnOnce() -> TokenStream,
+) -> TokenStream {
+    match overflow {
+        Overflow::Default => default(),
+        Overflow::Wrap => {
+            let method = format_ident!("wrapping_{op}");
+            quote! { self.#method(#args) }
+        }
+        Overflow::Saturate => {
+            let method = format_ident!("saturating_{op}");
+            quote! { self.#method(#args) }
+        }
+        Overflow::Panic => {
+            let method = format_ident!("overflowing_{op}");
+            quote! {
+                let (result, overflow) = self.#method(#args);
+                if overflow {
+                    ::core::panic!(#message);
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Generates the checked, wrapping, saturating and overflowing method families
+/// (mirroring the primitive integers) for addition, subtraction,
+/// multiplication, division, negation and (signed types only) absolute value.
+///
+/// Each family is built on its `overflowing_*` method. Multiplication and
+/// division go through `wide_type` (twice the width of `inner_type`), so the
+/// intermediate results cannot overflow before being rescaled. For 128-bit
+/// inner types, which have no wider type, they call the 256-bit helpers in
+/// `q_num::__private` instead.
+fn generate_overflow_methods(
+    vis: &Visibility,
+    inner_type: &Type,
+    wide_type: Option<&Type>,
+    signed: bool,
+) -> TokenStream {
+    let option = quote! { ::core::option::Option };
+    let bool = quote! { ::core::primitive::bool };
+    let (mul, div) = match wide_type {
+        Some(wide_type) => {
+            let in_range = quote! {
+                (<#inner_type>::MIN as #wide_type..=<#inner_type>::MAX as #wide_type)
+                    .contains(&bits)
+            };
+            (
+                quote! {
+                    let (lhs, rhs) = (self.to_left_bits(), rhs.to_left_bits());
+                    let product = (lhs as #wide_type) * (rhs as #wide_type);
+                    let bits = product >> (Self::FRAC_BITS + Self::PAD_BITS);
+                    (Self::from_left_bits(bits as #inner_type), !#in_range)
+                },
+                quote! {
+                    let (lhs, rhs) = (self.to_left_bits(), rhs.to_left_bits());
+                    let quotient = ((lhs as #wide_type) << Self::FRAC_BITS) / (rhs as #wide_type);
+                    let bits = quotient << Self::PAD_BITS;
+                    (Self::from_left(bits as #inner_type), !#in_range)
+                },
+            )
+        }
+        None => {
+            let (mul, div) = if signed {
+                (quote! { mul_i128 }, quote! { div_i128 })
+            } else {
+                (quote! { mul_u128 }, quote! { div_u128 })
+            };
+            (
+                quote! {
+                    let (bits, overflow) = ::q_num::__private::#mul(
+                        self.to_left_bits(),
+                        rhs.to_left_bits(),
+                        Self::FRAC_BITS + Self::PAD_BITS,
+                    );
+                    (Self::from_left_bits(bits), overflow)
+                },
+                quote! {
+                    let (bits, overflow) = ::q_num::__private::#div(
+                        self.to_left_bits(),
+                        rhs.to_left_bits(),
+                        Self::FRAC_BITS,
+                        Self::PAD_BITS,
+                    );
+                    (Self::from_left(bits), overflow)
+                },
+            )
+        }
+    };
+    // The saturated result of a multiplication or division that overflowed.
+    let saturated = if signed {
+        quote! {
+            if (self.0 < 0) != (rhs.0 < 0) { Self::MIN } else { Self::MAX }
+        }
+    } else {
+        quote! { Self::MAX }
+    };
+    let abs = if signed {
+        quote! {
+            /// Computes the absolute value of `self`.
+            #vis fn abs(self) -> Self {
+                Self::from_left(self.to_left_bits().abs())
+            }
+
+            /// Checked absolute value. Returns `None` if `self == MIN`.
+            #vis fn checked_abs(self) -> #option<Self> {
+                self.to_left_bits().checked_abs().map(Self::from_left)
+            }
+
+            /// Wrapping absolute value. Returns `MIN` if `self == MIN`.
+            #vis fn wrapping_abs(self) -> Self {
+                Self::from_left(self.to_left_bits().wrapping_abs())
+            }
+
+            /// Saturating absolute value. Returns `MAX` if `self == MIN`.
+            #vis fn saturating_abs(self) -> Self {
+                Self::from_left_bits(self.to_left_bits().saturating_abs())
+            }
+
+            /// Computes the absolute value of `self`, along with a boolean
+            /// indicating whether an overflow happened.
+            #vis fn overflowing_abs(self) -> (Self, #bool) {
+                let (bits, overflow) = self.to_left_bits().overflowing_abs();
+                (Self::from_left(bits), overflow)
+            }
+
+            /// Saturating negation. Returns `MAX` if `self == MIN`.
+            #vis fn saturating_neg(self) -> Self {
+                Self::from_left_bits(self.to_left_bits().saturating_neg())
+            }
+        }
+    } else {
+        quote! {}
+    };
+    quote! {
+        /// Checked addition. Returns `None` if overflow occurred.
+        #vis fn checked_add(self, rhs: Self) -> #option<Self> {
+            self.to_left_bits().checked_add(rhs.to_left_bits()).map(Self::from_left)
+        }
+
+        /// Wrapping addition. Wraps around at the numeric bounds.
+        #vis fn wrapping_add(self, rhs: Self) -> Self {
+            Self::from_left(self.to_left_bits().wrapping_add(rhs.to_left_bits()))
+        }
+
+        /// Saturating addition. Saturates at the numeric bounds.
+        #vis fn saturating_add(self, rhs: Self) -> Self {
+            Self::from_left_bits(self.to_left_bits().saturating_add(rhs.to_left_bits()))
+        }
+
+        /// Computes `self + rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        #vis fn overflowing_add(self, rhs: Self) -> (Self, #bool) {
+            let (bits, overflow) = self.to_left_bits().overflowing_add(rhs.to_left_bits());
+            (Self::from_left(bits), overflow)
+        }
+
+        /// Checked subtraction. Returns `None` if overflow occurred.
+        #vis fn checked_sub(self, rhs: Self) -> #option<Self> {
+            self.to_left_bits().checked_sub(rhs.to_left_bits()).map(Self::from_left)
+        }
+
+        /// Wrapping subtraction. Wraps around at the numeric bounds.
+        #vis fn wrapping_sub(self, rhs: Self) -> Self {
+            Self::from_left(self.to_left_bits().wrapping_sub(rhs.to_left_bits()))
+        }
+
+        /// Saturating subtraction. Saturates at the numeric bounds.
+        #vis fn saturating_sub(self, rhs: Self) -> Self {
+            Self::from_left_bits(self.to_left_bits().saturating_sub(rhs.to_left_bits()))
+        }
+
+        /// Computes `self - rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        #vis fn overflowing_sub(self, rhs: Self) -> (Self, #bool) {
+            let (bits, overflow) = self.to_left_bits().overflowing_sub(rhs.to_left_bits());
+            (Self::from_left(bits), overflow)
+        }
+
+        /// Checked multiplication. Returns `None` if overflow occurred.
+        #vis fn checked_mul(self, rhs: Self) -> #option<Self> {
+            match self.overflowing_mul(rhs) {
+                (product, false) => #option::Some(product),
+                (_, true) => #option::None,
+            }
+        }
+
+        /// Wrapping multiplication. Wraps around at the numeric bounds.
+        #vis fn wrapping_mul(self, rhs: Self) -> Self {
+            self.overflowing_mul(rhs).0
+        }
+
+        /// Saturating multiplication. Saturates at the numeric bounds.
+        #vis fn saturating_mul(self, rhs: Self) -> Self {
+            match self.overflowing_mul(rhs) {
+                (product, false) => product,
+                (_, true) => #saturated,
+            }
+        }
+
+        /// Computes `self * rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        ///
+        /// Multiplies using a wider intermediate, then shifts the product back
+        /// into place. The result is rounded toward negative infinity.
+        #vis fn overflowing_mul(self, rhs: Self) -> (Self, #bool) {
+            #mul
+        }
+
+        /// Checked division. Returns `None` if `rhs == 0` or overflow occurred.
+        #vis fn checked_div(self, rhs: Self) -> #option<Self> {
+            if rhs.0 == 0 {
+                return #option::None;
+            }
+            match self.overflowing_div(rhs) {
+                (quotient, false) => #option::Some(quotient),
+                (_, true) => #option::None,
+            }
+        }
+
+        /// Wrapping division. Wraps around at the numeric bounds.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `rhs == 0`.
+        #vis fn wrapping_div(self, rhs: Self) -> Self {
+            self.overflowing_div(rhs).0
+        }
+
+        /// Saturating division. Saturates at the numeric bounds.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `rhs == 0`.
+        #vis fn saturating_div(self, rhs: Self) -> Self {
+            match self.overflowing_div(rhs) {
+                (quotient, false) => quotient,
+                (_, true) => #saturated,
+            }
+        }
+
+        /// Computes `self / rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        ///
+        /// Divides using a wider intermediate, then shifts the quotient back
+        /// into place. The result is rounded toward zero.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `rhs == 0`.
+        #vis fn overflowing_div(self, rhs: Self) -> (Self, #bool) {
+            #div
+        }
+
+        /// Checked negation. Returns `None` if overflow occurred.
+        #vis fn checked_neg(self) -> #option<Self> {
+            self.to_left_bits().checked_neg().map(Self::from_left)
+        }
+
+        /// Wrapping negation. Wraps around at the numeric bounds.
+        #vis fn wrapping_neg(self) -> Self {
+            Self::from_left(self.to_left_bits().wrapping_neg())
+        }
+
+        /// Computes `-self`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        #vis fn overflowing_neg(self) -> (Self, #bool) {
+            let (bits, overflow) = self.to_left_bits().overflowing_neg();
+            (Self::from_left(bits), overflow)
+        }
+
+        #abs
+    }
+}
@@ -0,0 +1,44 @@
+//! Procedural macros for the [`q-num`] crate.
+//!
+//! The generated code refers to items defined in `q-num`, so depend on `q-num`
+//! and use the macros it re-exports instead of depending on this crate
+//! directly.
+//!
+//! [`q-num`]: https://crates.io/crates/q-num
+
+mod gen;
+mod limbs;
+mod literal;
+mod math;
+mod parse;
+mod types;
+
+use crate::gen::{generate, generate_all, generate_literal};
+use crate::parse::{Args, Inputs, LiteralInput};
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+#[proc_macro]
+pub fn define_q_num(input: TokenStream) -> TokenStream {
+    let inputs = parse_macro_input!(input as Inputs);
+    match generate_all(inputs) {
+        Ok(token_stream) => token_stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn q_num(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
+    let item = parse_macro_input!(item as DeriveInput);
+    match args.with_struct(item).and_then(generate) {
+        Ok(token_stream) => token_stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn q(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as LiteralInput);
+    generate_literal(input).into()
+}
@@ -0,0 +1,513 @@
+//! Generates types wider than 128 bits, whose inner value is stored in a
+//! `[u64; N]` array of little-endian limbs. They support fewer operations than
+//! the types stored in a primitive integer: the arithmetic is implemented in
+//! `q_num::__private` for addition, subtraction, negation and multiplication,
+//! along with comparison, formatting, parsing and `f64` conversions.
+
+use crate::gen::{
+    apply_cfgs, doc_separator, generate_from_f64_round, generate_try_from_float, op_body,
+    rounding_mode,
+};
+use crate::literal::limbs_literal;
+use crate::parse::{Input, Layout, Overflow};
+use crate::types::core_primitive_type;
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Ident, Visibility};
+
+pub fn generate_limbs(input: Input, limbs: usize) -> syn::Result<TokenStream> {
+    assert!(input.int_bits >= 1);
+    let Input {
+        attrs,
+        visibility: vis,
+        name,
+        signed,
+        int_bits,
+        frac_bits,
+        options,
+    } = input;
+    if options.layout == Layout::Right {
+        return Err(syn::Error::new(
+            name.span(),
+            "`layout = right` is not supported for formats wider than 128 bits",
+        ));
+    }
+    let used_bits = int_bits as u16 + frac_bits as u16;
+    let total_bits = 64 * limbs as u16;
+    // Below 64, so the padding is confined to the first limb.
+    let pad_bits = (total_bits - used_bits) as u8;
+    let bool = core_primitive_type("bool")?;
+    let u8 = core_primitive_type("u8")?;
+    let u16 = core_primitive_type("u16")?;
+    let u32 = core_primitive_type("u32")?;
+    let u64 = core_primitive_type("u64")?;
+    let f64 = core_primitive_type("f64")?;
+    let len = Literal::usize_unsuffixed(limbs);
+    let inner_type = quote! { [#u64; #len] };
+    let q_notation = if signed {
+        format!("Q{int_bits}.{frac_bits}")
+    } else {
+        format!("UQ{int_bits}.{frac_bits}")
+    };
+    let mut used_mask = vec![u64::MAX; limbs];
+    used_mask[0] <<= pad_bits;
+    let (min, max) = if signed {
+        let mut min = vec![0; limbs];
+        min[limbs - 1] = 1 << 63;
+        let mut max = used_mask.clone();
+        max[limbs - 1] >>= 1;
+        (min, max)
+    } else {
+        (vec![0; limbs], used_mask.clone())
+    };
+    let (used_mask, min, max) = (
+        limbs_literal(&used_mask),
+        limbs_literal(&min),
+        limbs_literal(&max),
+    );
+    let denominator = 2f64.powi(frac_bits as i32);
+    let (min_float, max_float, min_ulps, end_ulps) = if signed {
+        let x = 2f64.powi(int_bits as i32 - 1);
+        let ulps = 2f64.powi(used_bits as i32 - 1);
+        (-x, x - 1.0 / denominator, -ulps, ulps)
+    } else {
+        let x = 2f64.powi(int_bits as i32);
+        (0.0, x - 1.0 / denominator, 0.0, 2f64.powi(used_bits as i32))
+    };
+    let rounding = rounding_mode(options.rounding);
+    let doc = generate_doc(signed, int_bits, frac_bits, &q_notation, limbs);
+    let doc_separator = doc_separator(&attrs);
+    let from_f64_round = generate_from_f64_round(
+        &vis,
+        min_ulps,
+        end_ulps,
+        quote! { Self(::q_num::__private::limbs_from_f64(n, Self::PAD_BITS)) },
+    )?;
+    let try_from_float = generate_try_from_float(&name)?;
+    let overflow_methods = generate_overflow_methods(&vis, &len, signed);
+    let ops = generate_ops(&name, signed, options.overflow);
+    let items = quote! {
+        impl #name {
+            #vis const Q_NOTATION: &'static str = #q_notation;
+            #vis const SIGNED: #bool = #signed;
+            #vis const TOTAL_BITS: #u16 = #total_bits;
+            #vis const USED_BITS: #u16 = #used_bits;
+            #vis const INT_BITS: #u8 = #int_bits;
+            #vis const FRAC_BITS: #u8 = #frac_bits;
+            #vis const PAD_BITS: #u8 = #pad_bits;
+            #vis const USED_MASK: #inner_type = #used_mask;
+            #vis const MIN_FLOAT: #f64 = #min_float;
+            #vis const MAX_FLOAT: #f64 = #max_float;
+            #vis const MIN: Self = Self(#min);
+            #vis const MAX: Self = Self(#max);
+            #vis const DENOMINATOR: #f64 = #denominator;
+
+            /// The rounding mode used by `TryFrom<f64>`.
+            #vis const ROUNDING: ::q_num::RoundingMode = ::q_num::RoundingMode::#rounding;
+
+            /// Returns the inner value, as little-endian limbs.
+            #vis const fn to_bits(self) -> #inner_type { self.0 }
+
+            /// Builds a new instance using the provided little-endian limbs.
+            ///
+            /// Note: ensures unused bits (the padding) are zeroed out.
+            #vis const fn from_bits(bits: #inner_type) -> Self {
+                let mut bits = bits;
+                bits[0] &= Self::USED_MASK[0];
+                Self(bits)
+            }
+
+            #from_f64_round
+
+            /// Parses `s` like `FromStr`, but in a `const` context. This is what
+            /// the `q!` macro uses.
+            #vis const fn from_str_const(
+                s: &::core::primitive::str,
+            ) -> ::core::result::Result<Self, ::q_num::Error> {
+                let parsed =
+                    ::q_num::__private::parse_limbs(s.as_bytes(), Self::FRAC_BITS, Self::ROUNDING);
+                match parsed {
+                    ::core::result::Result::Ok((negative, magnitude)) => {
+                        match ::q_num::__private::limbs_from_parts(
+                            negative,
+                            magnitude,
+                            Self::SIGNED,
+                            Self::PAD_BITS,
+                        ) {
+                            ::core::option::Option::Some(bits) => ::core::result::Result::Ok(Self(bits)),
+                            ::core::option::Option::None => {
+                                ::core::result::Result::Err(::q_num::Error::Overflow)
+                            }
+                        }
+                    }
+                    ::core::result::Result::Err(error) => ::core::result::Result::Err(error),
+                }
+            }
+
+            #overflow_methods
+        }
+
+        impl ::core::cmp::PartialOrd for #name {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::option::Option::Some(::core::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        /// Compares the numeric values, from the most significant limb down.
+        impl ::core::cmp::Ord for #name {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                ::q_num::__private::limbs_cmp(&self.0, &other.0, Self::SIGNED)
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            /// Formats the exact value in decimal, honoring the precision
+            /// (rounding with ties to even), width, fill and alignment.
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::q_num::__private::display_limbs(
+                    self.0,
+                    Self::SIGNED,
+                    Self::FRAC_BITS,
+                    Self::PAD_BITS,
+                    f,
+                )
+            }
+        }
+
+        impl ::core::fmt::LowerExp for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::q_num::__private::lower_exp_limbs(
+                    self.0,
+                    Self::SIGNED,
+                    Self::FRAC_BITS,
+                    Self::PAD_BITS,
+                    f,
+                )
+            }
+        }
+
+        impl ::core::fmt::Debug for #name {
+            /// Formats the exact value and the format, e.g. `X(-1.25, Q64.192)`.
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::q_num::__private::debug_limbs(
+                    self.0,
+                    (Self::SIGNED, Self::FRAC_BITS, Self::PAD_BITS),
+                    ::core::stringify!(#name),
+                    Self::Q_NOTATION,
+                    f,
+                )
+            }
+        }
+
+        impl ::core::str::FromStr for #name {
+            type Err = ::q_num::Error;
+
+            /// Parses a decimal, binary (`0b`), octal (`0o`) or hexadecimal
+            /// (`0x`) number exactly, rounding with the `ROUNDING` mode.
+            fn from_str(s: &::core::primitive::str) -> ::core::result::Result<Self, Self::Err> {
+                Self::from_str_const(s)
+            }
+        }
+
+        #try_from_float
+
+        impl ::core::convert::From<#name> for #f64 {
+            /// Converts to the nearest `f64`.
+            fn from(value: #name) -> Self {
+                ::q_num::__private::limbs_to_f64(
+                    value.0,
+                    #name::SIGNED,
+                    #name::FRAC_BITS as #u32 + #name::PAD_BITS as #u32,
+                )
+            }
+        }
+
+        #ops
+    };
+    let items = apply_cfgs(&attrs, items);
+    Ok(quote! {
+        #(#attrs)*
+        #doc_separator
+        #[doc = #doc]
+        #[derive(
+            ::core::marker::Copy,
+            ::core::clone::Clone,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash,
+        )]
+        #vis struct #name(#inner_type);
+
+        #items
+    })
+}
+
+/// Returns the doc comment for the struct. The bounds are given as powers of
+/// two, since their exact decimal expansions can run to hundreds of digits.
+fn generate_doc(
+    signed: bool,
+    int_bits: u8,
+    frac_bits: u8,
+    q_notation: &str,
+    limbs: usize,
+) -> String {
+    let pow2 = |exponent: i32| {
+        if exponent == 0 {
+            "1".to_string()
+        } else {
+            format!("2^{exponent}")
+        }
+    };
+    let resolution = pow2(-(frac_bits as i32));
+    let (kind, sign_note, min, max) = if signed {
+        let x = pow2(int_bits as i32 - 1);
+        ("A signed", " (including the sign bit)", format!("-{x}"), x)
+    } else {
+        ("An unsigned", "", "0".to_string(), pow2(int_bits as i32))
+    };
+    format!(
+        " {kind} binary fixed-point number in `{q_notation}` format, with \
+         {int_bits} integer bits{sign_note} and {frac_bits} fractional bits, \
+         stored in `[u64; {limbs}]`.\n\n\
+         It ranges from {min} to {max} - {resolution} in steps of {resolution}."
+    )
+}
+
+/// Generates the checked, wrapping, saturating and overflowing method families
+/// for addition, subtraction, multiplication and negation. Each family is built
+/// on its `overflowing_*` method, which calls the limb arithmetic in
+/// `q_num::__private`.
+fn generate_overflow_methods(vis: &Visibility, len: &Literal, signed: bool) -> TokenStream {
+    let option = quote! { ::core::option::Option };
+    let bool = quote! { ::core::primitive::bool };
+    let u32 = quote! { ::core::primitive::u32 };
+    // The saturated results of the operations that overflowed.
+    let (add_saturated, sub_saturated, mul_saturated) = if signed {
+        (
+            quote! { if self.is_negative() { Self::MIN } else { Self::MAX } },
+            quote! { if self.is_negative() { Self::MIN } else { Self::MAX } },
+            quote! { if self.is_negative() != rhs.is_negative() { Self::MIN } else { Self::MAX } },
+        )
+    } else {
+        (
+            quote! { Self::MAX },
+            quote! { Self::MIN },
+            quote! { Self::MAX },
+        )
+    };
+    let signed_methods = if signed {
+        quote! {
+            fn is_negative(self) -> #bool {
+                self.0[#len - 1] >> 63 == 1
+            }
+
+            /// Saturating negation. Returns `MAX` if `self == MIN`.
+            #vis fn saturating_neg(self) -> Self {
+                match self.overflowing_neg() {
+                    (result, false) => result,
+                    (_, true) => Self::MAX,
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    quote! {
+        /// Checked addition. Returns `None` if overflow occurred.
+        #vis fn checked_add(self, rhs: Self) -> #option<Self> {
+            match self.overflowing_add(rhs) {
+                (sum, false) => #option::Some(sum),
+                (_, true) => #option::None,
+            }
+        }
+
+        /// Wrapping addition. Wraps around at the numeric bounds.
+        #vis fn wrapping_add(self, rhs: Self) -> Self {
+            self.overflowing_add(rhs).0
+        }
+
+        /// Saturating addition. Saturates at the numeric bounds.
+        #vis fn saturating_add(self, rhs: Self) -> Self {
+            match self.overflowing_add(rhs) {
+                (sum, false) => sum,
+                (_, true) => #add_saturated,
+            }
+        }
+
+        /// Computes `self + rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        #vis fn overflowing_add(self, rhs: Self) -> (Self, #bool) {
+            let (bits, overflow) = ::q_num::__private::limbs_add(self.0, rhs.0, Self::SIGNED);
+            (Self(bits), overflow)
+        }
+
+        /// Checked subtraction. Returns `None` if overflow occurred.
+        #vis fn checked_sub(self, rhs: Self) -> #option<Self> {
+            match self.overflowing_sub(rhs) {
+                (difference, false) => #option::Some(difference),
+                (_, true) => #option::None,
+            }
+        }
+
+        /// Wrapping subtraction. Wraps around at the numeric bounds.
+        #vis fn wrapping_sub(self, rhs: Self) -> Self {
+            self.overflowing_sub(rhs).0
+        }
+
+        /// Saturating subtraction. Saturates at the numeric bounds.
+        #vis fn saturating_sub(self, rhs: Self) -> Self {
+            match self.overflowing_sub(rhs) {
+                (difference, false) => difference,
+                (_, true) => #sub_saturated,
+            }
+        }
+
+        /// Computes `self - rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        #vis fn overflowing_sub(self, rhs: Self) -> (Self, #bool) {
+            let (bits, overflow) = ::q_num::__private::limbs_sub(self.0, rhs.0, Self::SIGNED);
+            (Self(bits), overflow)
+        }
+
+        /// Checked multiplication. Returns `None` if overflow occurred.
+        #vis fn checked_mul(self, rhs: Self) -> #option<Self> {
+            match self.overflowing_mul(rhs) {
+                (product, false) => #option::Some(product),
+                (_, true) => #option::None,
+            }
+        }
+
+        /// Wrapping multiplication. Wraps around at the numeric bounds.
+        #vis fn wrapping_mul(self, rhs: Self) -> Self {
+            self.overflowing_mul(rhs).0
+        }
+
+        /// Saturating multiplication. Saturates at the numeric bounds.
+        #vis fn saturating_mul(self, rhs: Self) -> Self {
+            match self.overflowing_mul(rhs) {
+                (product, false) => product,
+                (_, true) => #mul_saturated,
+            }
+        }
+
+        /// Computes `self * rhs`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        ///
+        /// Multiplies the limbs into a double-width product, then shifts it back
+        /// into place. The result is rounded toward negative infinity.
+        #vis fn overflowing_mul(self, rhs: Self) -> (Self, #bool) {
+            let (bits, overflow) = ::q_num::__private::limbs_mul(
+                self.0,
+                rhs.0,
+                Self::SIGNED,
+                Self::FRAC_BITS as #u32 + Self::PAD_BITS as #u32,
+            );
+            (Self::from_bits(bits), overflow)
+        }
+
+        /// Checked negation. Returns `None` if overflow occurred.
+        #vis fn checked_neg(self) -> #option<Self> {
+            match self.overflowing_neg() {
+                (result, false) => #option::Some(result),
+                (_, true) => #option::None,
+            }
+        }
+
+        /// Wrapping negation. Wraps around at the numeric bounds.
+        #vis fn wrapping_neg(self) -> Self {
+            self.overflowing_neg().0
+        }
+
+        /// Computes `-self`, along with a boolean indicating whether an
+        /// overflow happened. If an overflow happened, the wrapped value is
+        /// returned.
+        #vis fn overflowing_neg(self) -> (Self, #bool) {
+            let zero = [0; #len];
+            let (bits, overflow) = ::q_num::__private::limbs_sub(zero, self.0, Self::SIGNED);
+            (Self(bits), overflow)
+        }
+
+        #signed_methods
+    }
+}
+
+/// Generates `+`, `-`, `*`, unary `-` (signed types only) and their compound
+/// assignment forms, following the `overflow` option like the operators of the
+/// other types.
+fn generate_ops(name: &Ident, signed: bool, overflow: Overflow) -> TokenStream {
+    let body = |op: &str, message: &str, args: TokenStream| {
+        let method = format_ident!("overflowing_{op}");
+        op_body(overflow, op, message, &args.clone(), || {
+            quote! {
+                let (result, overflow) = self.#method(#args);
+                ::core::debug_assert!(!overflow, #message);
+                result
+            }
+        })
+    };
+    let add = body("add", "attempt to add with overflow", quote! { rhs });
+    let sub = body("sub", "attempt to subtract with overflow", quote! { rhs });
+    let mul = body("mul", "attempt to multiply with overflow", quote! { rhs });
+    let neg = if signed {
+        let neg = body("neg", "attempt to negate with overflow", quote! {});
+        quote! {
+            impl ::core::ops::Neg for #name {
+                type Output = Self;
+
+                fn neg(self) -> Self::Output {
+                    #neg
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    quote! {
+        impl ::core::ops::Add for #name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                #add
+            }
+        }
+
+        impl ::core::ops::Sub for #name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                #sub
+            }
+        }
+
+        impl ::core::ops::Mul for #name {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self::Output {
+                #mul
+            }
+        }
+
+        #neg
+
+        impl ::core::ops::AddAssign for #name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ::core::ops::SubAssign for #name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl ::core::ops::MulAssign for #name {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+    }
+}
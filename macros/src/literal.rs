@@ -1,14 +1,21 @@
-use proc_macro2::Literal;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
 
 /// Converts the provided value to a hex literal.
 ///
 /// Note: this function will panic if the value cannot be parsed as a
 /// `proc_macro2::Literal`. This is the best choice, because that would be a bug
 /// outside of this crate's control.
-pub fn hex_literal(value: u64) -> Literal {
+pub fn hex_literal(value: u128) -> Literal {
     format!("{:#X}", value).parse().unwrap()
 }
 
+/// Converts little-endian limbs to an array literal, e.g. `[0xFF, 0x0]`.
+pub fn limbs_literal(limbs: &[u64]) -> TokenStream {
+    let limbs = limbs.iter().map(|&limb| hex_literal(limb as u128));
+    quote! { [#(#limbs),*] }
+}
+
 /// e.g. 0b1111_1000 if used_bits is 5 and pad_bits is 3
 pub fn used_mask_literal(total_bits: u8, pad_bits: u8) -> Literal {
     match total_bits {
@@ -16,30 +23,41 @@ pub fn used_mask_literal(total_bits: u8, pad_bits: u8) -> Literal {
             let mut x: u8 = 0xFF;
             x >>= pad_bits;
             x <<= pad_bits;
-            hex_literal(x as u64)
+            hex_literal(x as u128)
         }
         16 => {
             let mut x: u16 = 0xFFFF;
             x >>= pad_bits;
             x <<= pad_bits;
-            hex_literal(x as u64)
+            hex_literal(x as u128)
         }
         32 => {
             let mut x: u32 = 0xFFFF_FFFF;
             x >>= pad_bits;
             x <<= pad_bits;
-            hex_literal(x as u64)
+            hex_literal(x as u128)
         }
         64 => {
             let mut x: u64 = 0xFFFF_FFFF_FFFF_FFFF;
             x >>= pad_bits;
             x <<= pad_bits;
+            hex_literal(x as u128)
+        }
+        128 => {
+            let mut x: u128 = u128::MAX;
+            x >>= pad_bits;
+            x <<= pad_bits;
             hex_literal(x)
         }
         _ => panic!(),
     }
 }
 
+/// e.g. 0b0001_1111 if used_bits is 5, for the right layout
+pub fn low_mask_literal(used_bits: u8) -> Literal {
+    hex_literal(u128::MAX >> (128 - used_bits as u32))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +83,19 @@ mod tests {
         let s = quote! { #literal }.to_string();
         assert_eq!(s, "0xFFFFF800");
     }
+
+    #[test]
+    fn test_used_mask_literal_128_28() {
+        let literal = used_mask_literal(128, 28);
+        let s = quote! { #literal }.to_string();
+        assert_eq!(s, "0xFFFFFFFFFFFFFFFFFFFFFFFFF0000000");
+    }
+
+    #[test]
+    fn test_low_mask_literal() {
+        let literal = low_mask_literal(6);
+        assert_eq!(quote! { #literal }.to_string(), "0x3F");
+        let literal = low_mask_literal(128);
+        assert_eq!(quote! { #literal }.to_string(), format!("{:#X}", u128::MAX));
+    }
 }
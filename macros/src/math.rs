@@ -0,0 +1,123 @@
+/// How the inner value of a format with `used_bits` bits is stored.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Storage {
+    /// In the narrowest primitive integer of this many bits (up to 128).
+    Primitive(u8),
+    /// In this many `u64` limbs, when more than 128 bits are needed.
+    Limbs(usize),
+}
+
+pub fn storage(used_bits: u16) -> Storage {
+    match u8::try_from(used_bits)
+        .ok()
+        .and_then(power_of_two_bit_length)
+    {
+        Some(n) => Storage::Primitive(n),
+        None => Storage::Limbs(used_bits.div_ceil(64) as usize),
+    }
+}
+
+/// Returns the exact decimal representation of `magnitude / 2^frac_bits`,
+/// negated if `negative` is true. (Every such value has a finite decimal
+/// expansion.)
+pub fn exact_decimal(negative: bool, magnitude: u128, frac_bits: u8) -> String {
+    let mut s = String::new();
+    if negative && magnitude != 0 {
+        s.push('-');
+    }
+    s.push_str(&(magnitude >> frac_bits).to_string());
+    let mask = (1u128 << frac_bits) - 1;
+    let mut frac = magnitude & mask;
+    if frac != 0 {
+        s.push('.');
+    }
+    while frac != 0 {
+        // `frac * 10` can need more than 128 bits, so multiply the two halves
+        // separately.
+        let low = (frac as u64 as u128) * 10;
+        let high = (frac >> 64) * 10 + (low >> 64);
+        let product = (high << 64) | (low as u64 as u128);
+        let digit = ((high >> 64) << (128 - frac_bits as u32)) | (product >> frac_bits);
+        s.push(char::from(b'0' + digit as u8));
+        frac = product & mask;
+    }
+    s
+}
+
+/// Return number of bits (corresponding to a power of two) required to
+/// represent `bits`. Successful output can be 8, 16, 32, 64, or 128.
+#[rustfmt::skip]
+pub fn power_of_two_bit_length(bits: u8) -> Option<u8> {
+    match bits {
+         0 ..=  8 => Some(8),
+         9 ..= 16 => Some(16),
+        17 ..= 32 => Some(32),
+        33 ..= 64 => Some(64),
+        65 ..=128 => Some(128),
+                _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exact_decimal, power_of_two_bit_length, storage, Storage};
+
+    #[test]
+    fn test_exact_decimal() {
+        assert_eq!(exact_decimal(false, 0b10111, 2), "5.75");
+        assert_eq!(exact_decimal(true, 128, 2), "-32");
+        assert_eq!(exact_decimal(true, 0, 2), "0");
+        assert_eq!(exact_decimal(false, 1, 20), "0.00000095367431640625");
+        assert_eq!(
+            exact_decimal(false, (1 << 59) - 1, 20),
+            "549755813887.99999904632568359375"
+        );
+        let s = exact_decimal(false, u128::MAX >> 1, 127);
+        assert!(s.starts_with("0.99999999999999999999999999999999999999"));
+        assert_eq!(s.len(), 129);
+    }
+
+    #[test]
+    fn test_storage() {
+        assert_eq!(storage(12), Storage::Primitive(16));
+        assert_eq!(storage(128), Storage::Primitive(128));
+        assert_eq!(storage(129), Storage::Limbs(3));
+        assert_eq!(storage(256), Storage::Limbs(4));
+        assert_eq!(storage(510), Storage::Limbs(8));
+    }
+
+    #[test]
+    fn power_of_two_bit_length_near_8() {
+        assert_eq!(power_of_two_bit_length(7), Some(8));
+        assert_eq!(power_of_two_bit_length(8), Some(8));
+        assert_eq!(power_of_two_bit_length(9), Some(16));
+    }
+
+    #[test]
+    fn power_of_two_bit_length_near_16() {
+        assert_eq!(power_of_two_bit_length(15), Some(16));
+        assert_eq!(power_of_two_bit_length(16), Some(16));
+        assert_eq!(power_of_two_bit_length(17), Some(32));
+    }
+
+    #[test]
+    fn power_of_two_bit_length_near_32() {
+        assert_eq!(power_of_two_bit_length(31), Some(32));
+        assert_eq!(power_of_two_bit_length(32), Some(32));
+        assert_eq!(power_of_two_bit_length(33), Some(64));
+    }
+
+    #[test]
+    fn power_of_two_bit_length_near_64() {
+        assert_eq!(power_of_two_bit_length(63), Some(64));
+        assert_eq!(power_of_two_bit_length(64), Some(64));
+        assert_eq!(power_of_two_bit_length(65), Some(128));
+    }
+
+    #[test]
+    fn power_of_two_bit_length_near_128() {
+        assert_eq!(power_of_two_bit_length(127), Some(128));
+        assert_eq!(power_of_two_bit_length(128), Some(128));
+        assert_eq!(power_of_two_bit_length(129), None);
+    }
+}
@@ -0,0 +1,658 @@
+use crate::math::{storage, Storage};
+use proc_macro2::Span;
+use std::fmt;
+use syn::parse;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    Attribute, Data, DataStruct, DeriveInput, Fields, Ident, Lit, LitInt, Token, Type, Visibility,
+};
+
+pub struct Input {
+    pub attrs: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub name: Ident,
+    pub signed: bool,
+    pub int_bits: u8,
+    pub frac_bits: u8,
+    pub options: Options,
+}
+
+/// Optional settings given as trailing `key = value` pairs.
+#[derive(Default)]
+pub struct Options {
+    pub overflow: Overflow,
+    pub rounding: Rounding,
+    pub layout: Layout,
+    pub storage: Option<StorageType>,
+}
+
+/// What the plain operators (`+`, `-`, `*`, `/` and unary `-`) do on overflow.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Overflow {
+    /// Like the primitive integers: panic in debug builds, wrap otherwise.
+    #[default]
+    Default,
+    /// `overflow = wrap`
+    Wrap,
+    /// `overflow = saturate`
+    Saturate,
+    /// `overflow = panic`: panic in all builds.
+    Panic,
+}
+
+/// The default rounding mode for conversions, i.e. a `q_num::RoundingMode`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    /// `rounding = nearest_even`
+    NearestTiesEven,
+    /// `rounding = nearest_away`
+    NearestTiesAway,
+    /// `rounding = floor`
+    Floor,
+    /// `rounding = ceil`
+    Ceil,
+    /// `rounding = toward_zero`
+    #[default]
+    TowardZero,
+}
+
+/// Where the used bits sit within the inner type.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Layout {
+    /// `layout = left`: the padding is below the value and zeroed.
+    #[default]
+    Left,
+    /// `layout = right`: the padding is above the value and sign-extended (or
+    /// zeroed for unsigned types).
+    Right,
+}
+
+/// An explicit inner type, e.g. `storage = i32`, overriding the narrowest one
+/// that holds the used bits.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageType {
+    pub signed: bool,
+    pub bits: u8,
+    pub span: Span,
+}
+
+impl StorageType {
+    /// Checks that the type has the signedness of `format` and holds its used
+    /// bits.
+    fn validate(self, format: Format) -> parse::Result<()> {
+        if self.signed != format.signed {
+            let expected = if format.signed {
+                "Expected a signed storage type for a `Q` format"
+            } else {
+                "Expected an unsigned storage type for a `UQ` format"
+            };
+            return Err(parse::Error::new(self.span, expected));
+        }
+        if (self.bits as u16) < format.used_bits() {
+            return Err(parse::Error::new(
+                self.span,
+                format!(
+                    "The storage type has {} bits, but the format uses {}",
+                    self.bits,
+                    format.used_bits()
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A Q format, e.g. `Q10.4`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Format {
+    pub signed: bool,
+    pub int_bits: u8,
+    pub frac_bits: u8,
+}
+
+impl Format {
+    /// Returns the format that holds the exact product of a value in `self` and
+    /// a value in `rhs`, e.g. `Q10.6` for `Q6.2 * Q4.4`, or `None` if the
+    /// number of bits overflows.
+    pub fn product(self, rhs: Format) -> Option<Format> {
+        Some(Format {
+            signed: self.signed || rhs.signed,
+            int_bits: self.int_bits.checked_add(rhs.int_bits)?,
+            frac_bits: self.frac_bits.checked_add(rhs.frac_bits)?,
+        })
+    }
+
+    /// Returns the number of bits, which can exceed 255.
+    pub fn used_bits(self) -> u16 {
+        self.int_bits as u16 + self.frac_bits as u16
+    }
+
+    /// Returns whether every value in `self` is exactly representable in `to`.
+    pub fn is_lossless_into(self, to: Format) -> bool {
+        let int_ok = match (self.signed, to.signed) {
+            (true, true) | (false, false) => to.int_bits >= self.int_bits,
+            (false, true) => to.int_bits > self.int_bits,
+            (true, false) => false,
+        };
+        int_ok && to.frac_bits >= self.frac_bits
+    }
+}
+
+/// Formats as Q notation, e.g. `Q10.4` or `UQ10.4`.
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = if self.signed { "Q" } else { "UQ" };
+        write!(f, "{prefix}{}.{}", self.int_bits, self.frac_bits)
+    }
+}
+
+/// One or more type definitions, as given to `define_q_num!`.
+pub struct Inputs(pub Vec<Input>);
+
+/// Parses either a single definition (see `Input`) or a list of definitions,
+/// each terminated by a semicolon, for example:
+/// - `define_q_num! { pub Sample: Q1.15; Acc: Q16.48, overflow = wrap; }'
+impl Parse for Inputs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let fork = input.fork();
+        fork.call(Attribute::parse_outer)?;
+        fork.parse::<Visibility>()?;
+        fork.parse::<Ident>()?;
+        if !fork.peek(Token![:]) {
+            return Ok(Inputs(vec![input.parse()?]));
+        }
+        let mut inputs = Vec::new();
+        while !input.is_empty() {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let visibility = input.parse()?;
+            let name: Ident = input.parse()?;
+            input.parse::<Token![:]>()?;
+            let args: Args = input.parse()?;
+            input.parse::<Token![;]>()?;
+            inputs.push(args.into_input(attrs, visibility, name));
+        }
+        Ok(Inputs(inputs))
+    }
+}
+
+impl Input {
+    pub fn format(&self) -> Format {
+        Format {
+            signed: self.signed,
+            int_bits: self.int_bits,
+            frac_bits: self.frac_bits,
+        }
+    }
+}
+
+/// Parses, for example:
+/// - `define_q_num!(MyNum, Q10.4)'
+/// - `define_q_num!(pub MyNum, UQ10.4)'
+/// - `define_q_num!(pub(crate) MyNum, UQ10.4)'
+/// - `define_q_num!(MyNum, Q10.4, overflow = saturate)'
+/// - `define_q_num!(MyNum, Q10.4, rounding = nearest_even)'
+/// - `define_q_num!(MyNum, UQ4.2, layout = right)'
+/// - `define_q_num!(MyNum, Q4.2, storage = i32)'
+/// - `define_q_num!(MyNum, Q6.2 * Q4.4)', i.e. `Q10.6'
+/// - `define_q_num!(/// Docs\n #[repr(transparent)] MyNum, Q10.4)'
+impl Parse for Input {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let visibility = input.parse()?;
+        let name: Ident = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let args: Args = input.parse()?;
+        Ok(args.into_input(attrs, visibility, name))
+    }
+}
+
+/// The format and options, i.e. everything after the name, e.g.
+/// `Q6.2 * Q4.4, overflow = wrap`.
+pub struct Args {
+    pub format: Format,
+    pub options: Options,
+}
+
+impl Args {
+    fn into_input(self, attrs: Vec<Attribute>, visibility: Visibility, name: Ident) -> Input {
+        Input {
+            attrs,
+            visibility,
+            name,
+            signed: self.format.signed,
+            int_bits: self.format.int_bits,
+            frac_bits: self.format.frac_bits,
+            options: self.options,
+        }
+    }
+
+    /// Combines the arguments of `#[q_num(...)]` with the struct it is applied
+    /// to, e.g. `pub struct Angle;` or `pub struct Angle(i16);`. A declared
+    /// field must have the generated inner type.
+    pub fn with_struct(self, item: DeriveInput) -> parse::Result<Input> {
+        let expected = match storage_of(self.format, &self.options) {
+            Storage::Primitive(bits) if self.format.signed => format!("i{bits}"),
+            Storage::Primitive(bits) => format!("u{bits}"),
+            Storage::Limbs(limbs) => format!("[u64; {limbs}]"),
+        };
+        match &item.data {
+            Data::Struct(DataStruct {
+                fields: Fields::Unit,
+                ..
+            }) => {}
+            Data::Struct(DataStruct {
+                fields: Fields::Unnamed(fields),
+                ..
+            }) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                if type_name(ty) != expected.replace(' ', "") {
+                    return Err(parse::Error::new_spanned(
+                        ty,
+                        format!("Expected `{expected}`, the inner type of {}", self.format),
+                    ));
+                }
+            }
+            Data::Struct(DataStruct { fields, .. }) => {
+                return Err(parse::Error::new_spanned(
+                    fields,
+                    format!("Expected a unit struct or a tuple struct with one `{expected}` field"),
+                ))
+            }
+            _ => return Err(parse::Error::new_spanned(&item.ident, "Expected a struct")),
+        }
+        if !item.generics.params.is_empty() || item.generics.where_clause.is_some() {
+            return Err(parse::Error::new_spanned(
+                &item.generics,
+                "Generic parameters are not supported",
+            ));
+        }
+        Ok(self.into_input(item.attrs, item.vis, item.ident))
+    }
+}
+
+/// Returns how a value in `format` is stored: in the primitive integer given
+/// by the `storage` option, or else in the narrowest one that holds it.
+pub fn storage_of(format: Format, options: &Options) -> Storage {
+    match options.storage {
+        Some(storage) => Storage::Primitive(storage.bits),
+        None => storage(format.used_bits()),
+    }
+}
+
+/// Returns the type without whitespace or a `core::primitive`/`std::primitive`
+/// path, e.g. `i16` for `::core::primitive::i16`.
+fn type_name(ty: &Type) -> String {
+    let name = quote::quote!(#ty).to_string().replace(' ', "");
+    let name = name.trim_start_matches("::");
+    let name = name.strip_prefix("core::primitive::").unwrap_or(name);
+    let name = name.strip_prefix("std::primitive::").unwrap_or(name);
+    name.to_string()
+}
+
+/// Parses, for example, `Q10.4`, `Q6.2 * Q4.4` or `UQ10.4, overflow = wrap`.
+impl Parse for Args {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let mut format: Format = input.parse()?;
+        while input.peek(Token![*]) {
+            let star = input.parse::<Token![*]>()?;
+            let rhs = input.parse()?;
+            format = format
+                .product(rhs)
+                .ok_or_else(|| parse::Error::new(star.span, "Too many bits in product"))?;
+        }
+        let options: Options = input.parse()?;
+        if let Some(storage) = options.storage {
+            storage.validate(format)?;
+        }
+        Ok(Args { format, options })
+    }
+}
+
+/// Parses, for example, `Q10.4` or `UQ10.4`.
+///
+/// "UQ" -> signed = false
+/// "Q" -> signed = true
+impl Parse for Format {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let next_token = input.parse::<Ident>()?.to_string();
+        let (signed, int_bits) = if next_token.starts_with("UQ") {
+            let rest = next_token.strip_prefix("UQ").unwrap();
+            (false, parse_int_bits(rest)?)
+        } else if next_token.starts_with('Q') {
+            let rest = next_token.strip_prefix('Q').unwrap();
+            (true, parse_int_bits(rest)?)
+        } else {
+            return Err(parse::Error::new(Span::call_site(), "Expected UQ or Q"));
+        };
+        input.parse::<Token![.]>()?;
+        let frac_bits = input.parse::<LitInt>()?.base10_parse()?;
+        Ok(Format {
+            signed,
+            int_bits,
+            frac_bits,
+        })
+    }
+}
+
+/// Parses zero or more `, key = value` pairs (and an optional trailing comma),
+/// up to the end of the input or a semicolon.
+impl Parse for Options {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let mut options = Options::default();
+        let mut overflow = None;
+        let mut rounding = None;
+        let mut layout = None;
+        let mut storage = None;
+        while !input.is_empty() && !input.peek(Token![;]) {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() || input.peek(Token![;]) {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "overflow" => set_once(&mut overflow, &key, parse_overflow(&value)?)?,
+                "rounding" => set_once(&mut rounding, &key, parse_rounding(&value)?)?,
+                "layout" => set_once(&mut layout, &key, parse_layout(&value)?)?,
+                "storage" => set_once(&mut storage, &key, parse_storage(&value)?)?,
+                _ => {
+                    return Err(parse::Error::new(
+                        key.span(),
+                        "Expected `overflow`, `rounding`, `layout` or `storage`",
+                    ))
+                }
+            }
+        }
+        if let Some(overflow) = overflow {
+            options.overflow = overflow;
+        }
+        if let Some(rounding) = rounding {
+            options.rounding = rounding;
+        }
+        if let Some(layout) = layout {
+            options.layout = layout;
+        }
+        options.storage = storage;
+        Ok(options)
+    }
+}
+
+/// The input of `q!`: a type and a number, e.g. `Coeff, -0.7071`.
+pub struct LiteralInput {
+    pub ty: Type,
+    /// The number, as accepted by the parser in `q_num`.
+    pub value: String,
+}
+
+/// Parses, for example:
+/// - `q!(Coeff, 0.7071)'
+/// - `q!(Coeff, -1e-3)'
+/// - `q!(Coeff, "0x1.8p-1")'
+impl Parse for LiteralInput {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let negative = input.parse::<Option<Token![-]>>()?.is_some();
+        let lit: Lit = input.parse()?;
+        let digits = match &lit {
+            Lit::Int(lit) if lit.suffix().is_empty() => lit.base10_digits().to_string(),
+            Lit::Float(lit) if lit.suffix().is_empty() => lit.base10_digits().to_string(),
+            Lit::Str(lit) if !negative => lit.value(),
+            _ => {
+                return Err(parse::Error::new(
+                    lit.span(),
+                    "Expected a number without a suffix, or a string",
+                ))
+            }
+        };
+        input.parse::<Option<Token![,]>>()?;
+        let value = if negative {
+            format!("-{digits}")
+        } else {
+            digits
+        };
+        Ok(LiteralInput { ty, value })
+    }
+}
+
+/// Stores `value` in `slot`, rejecting an option given more than once.
+fn set_once<T>(slot: &mut Option<T>, key: &Ident, value: T) -> parse::Result<()> {
+    if slot.is_some() {
+        return Err(parse::Error::new(
+            key.span(),
+            format!("Duplicate option `{key}`"),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn parse_overflow(value: &Ident) -> parse::Result<Overflow> {
+    match value.to_string().as_str() {
+        "wrap" => Ok(Overflow::Wrap),
+        "saturate" => Ok(Overflow::Saturate),
+        "panic" => Ok(Overflow::Panic),
+        _ => Err(parse::Error::new(
+            value.span(),
+            "Expected `wrap`, `saturate` or `panic`",
+        )),
+    }
+}
+
+fn parse_int_bits(input: &str) -> Result<u8, parse::Error> {
+    match input.parse() {
+        Ok(x) => Ok(x),
+        Err(_) => Err(parse::Error::new(Span::call_site(), "Expected integer")),
+    }
+}
+
+fn parse_rounding(value: &Ident) -> parse::Result<Rounding> {
+    match value.to_string().as_str() {
+        "nearest_even" => Ok(Rounding::NearestTiesEven),
+        "nearest_away" => Ok(Rounding::NearestTiesAway),
+        "floor" => Ok(Rounding::Floor),
+        "ceil" => Ok(Rounding::Ceil),
+        "toward_zero" => Ok(Rounding::TowardZero),
+        _ => Err(parse::Error::new(
+            value.span(),
+            "Expected `nearest_even`, `nearest_away`, `floor`, `ceil` or `toward_zero`",
+        )),
+    }
+}
+
+fn parse_layout(value: &Ident) -> parse::Result<Layout> {
+    match value.to_string().as_str() {
+        "left" => Ok(Layout::Left),
+        "right" => Ok(Layout::Right),
+        _ => Err(parse::Error::new(
+            value.span(),
+            "Expected `left` or `right`",
+        )),
+    }
+}
+
+fn parse_storage(value: &Ident) -> parse::Result<StorageType> {
+    let (signed, bits) = match value.to_string().as_str() {
+        "i8" => (true, 8),
+        "i16" => (true, 16),
+        "i32" => (true, 32),
+        "i64" => (true, 64),
+        "i128" => (true, 128),
+        "u8" => (false, 8),
+        "u16" => (false, 16),
+        "u32" => (false, 32),
+        "u64" => (false, 64),
+        "u128" => (false, 128),
+        _ => {
+            return Err(parse::Error::new(
+                value.span(),
+                "Expected a primitive integer type, e.g. `i32` or `u32`",
+            ))
+        }
+    };
+    Ok(StorageType {
+        signed,
+        bits,
+        span: value.span(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_int_bits_1() {
+        let x = parse_int_bits("10");
+        assert!(x.is_ok());
+    }
+
+    #[test]
+    fn test_parse_int_bits_2() {
+        let x = parse_int_bits("31x");
+        assert!(x.is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let input: Input = syn::parse_str("X, Q2.14, overflow = saturate,").unwrap();
+        assert_eq!(input.options.overflow, Overflow::Saturate);
+        let input: Input = syn::parse_str("X, Q2.14, rounding = floor, overflow = wrap").unwrap();
+        assert_eq!(input.options.overflow, Overflow::Wrap);
+        assert_eq!(input.options.rounding, Rounding::Floor);
+        let input: Input = syn::parse_str("X, Q2.14").unwrap();
+        assert_eq!(input.options.overflow, Overflow::Default);
+        assert_eq!(input.options.rounding, Rounding::TowardZero);
+        assert_eq!(input.options.layout, Layout::Left);
+        let input: Input = syn::parse_str("X, UQ4.2, layout = right").unwrap();
+        assert_eq!(input.options.layout, Layout::Right);
+        assert!(input.options.storage.is_none());
+        let input: Input = syn::parse_str("X, Q4.2, storage = i32").unwrap();
+        let storage = input.options.storage.unwrap();
+        assert_eq!((storage.signed, storage.bits), (true, 32));
+    }
+
+    #[test]
+    fn test_parse_options_invalid() {
+        assert!(syn::parse_str::<Input>("X, Q2.14, overflow = clamp").is_err());
+        assert!(syn::parse_str::<Input>("X, Q2.14, overflows = wrap").is_err());
+        assert!(syn::parse_str::<Input>("X, Q2.14, rounding = nearest").is_err());
+        assert!(syn::parse_str::<Input>("X, Q2.14, layout = middle").is_err());
+        assert!(syn::parse_str::<Input>("X, Q2.14, storage = f32").is_err());
+        assert!(syn::parse_str::<Input>("X, Q2.14, storage = u32").is_err());
+        assert!(syn::parse_str::<Input>("X, UQ2.14, storage = i32").is_err());
+        assert!(syn::parse_str::<Input>("X, Q6.4, storage = i8").is_err());
+        assert!(syn::parse_str::<Input>("X, Q64.192, storage = i128").is_err());
+        assert!(syn::parse_str::<Input>("X, Q2.14, overflow = wrap, overflow = panic").is_err());
+    }
+
+    #[test]
+    fn test_parse_product() {
+        let input: Input = syn::parse_str("X, Q6.2 * UQ4.4").unwrap();
+        assert!(input.signed);
+        assert_eq!((input.int_bits, input.frac_bits), (10, 6));
+        let input: Input = syn::parse_str("X, UQ1.15 * UQ1.15 * UQ2.0, overflow = wrap").unwrap();
+        assert!(!input.signed);
+        assert_eq!((input.int_bits, input.frac_bits), (4, 30));
+        assert!(syn::parse_str::<Input>("X, Q200.0 * Q100.0").is_err());
+    }
+
+    #[test]
+    fn test_parse_visibility() {
+        let input: Input = syn::parse_str("X, Q6.2").unwrap();
+        assert!(matches!(input.visibility, Visibility::Inherited));
+        let input: Input = syn::parse_str("pub(in crate::a) X, Q6.2").unwrap();
+        assert!(matches!(input.visibility, Visibility::Restricted(_)));
+        let input: Input = syn::parse_str("pub X, Q6.2").unwrap();
+        assert!(matches!(input.visibility, Visibility::Public(_)));
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let input: Input = syn::parse_str("/// Docs\n#[cfg(test)] pub X, Q6.2").unwrap();
+        assert_eq!(input.attrs.len(), 2);
+        assert!(input.attrs[0].path().is_ident("doc"));
+        assert!(input.attrs[1].path().is_ident("cfg"));
+    }
+
+    #[test]
+    fn test_parse_attribute_form() {
+        let args: Args = syn::parse_str("UQ6.2, overflow = wrap").unwrap();
+        let item = syn::parse_str("/// Docs\npub(crate) struct Angle;").unwrap();
+        let input = args.with_struct(item).unwrap();
+        assert_eq!(input.name, "Angle");
+        assert!(!input.signed);
+        assert_eq!((input.int_bits, input.frac_bits), (6, 2));
+        assert_eq!(input.options.overflow, Overflow::Wrap);
+        assert_eq!(input.attrs.len(), 1);
+        assert!(matches!(input.visibility, Visibility::Restricted(_)));
+        let args: Args = syn::parse_str("Q10.6").unwrap();
+        let item = syn::parse_str("struct Angle(::core::primitive::i16);").unwrap();
+        assert!(args.with_struct(item).is_ok());
+        let args: Args = syn::parse_str("UQ4.2, storage = u32").unwrap();
+        assert!(args
+            .with_struct(syn::parse_str("struct A(u32);").unwrap())
+            .is_ok());
+        let args: Args = syn::parse_str("Q64.192").unwrap();
+        assert!(args
+            .with_struct(syn::parse_str("struct A([u64; 4]);").unwrap())
+            .is_ok());
+        for item in [
+            "struct Angle(i16);",
+            "struct Angle(u8);",
+            "struct Angle(i8, i8);",
+            "struct Angle { x: i8 }",
+            "struct Angle<T>;",
+            "enum Angle {}",
+        ] {
+            let args: Args = syn::parse_str("Q6.2").unwrap();
+            assert!(args.with_struct(syn::parse_str(item).unwrap()).is_err());
+        }
+        let args: Args = syn::parse_str("Q6.2").unwrap();
+        let error = args.with_struct(syn::parse_str("struct Angle(i16);").unwrap());
+        let error = error.err().unwrap().to_string();
+        assert_eq!(error, "Expected `i8`, the inner type of Q6.2");
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let inputs: Inputs = syn::parse_str(
+            "/// Docs\n pub Sample: Q1.15; Coeff: Q2.30, overflow = wrap,; Acc: UQ16.48;",
+        )
+        .unwrap();
+        let names: Vec<_> = inputs.0.iter().map(|i| i.name.to_string()).collect();
+        assert_eq!(names, ["Sample", "Coeff", "Acc"]);
+        assert_eq!(inputs.0[0].attrs.len(), 1);
+        assert_eq!(inputs.0[1].options.overflow, Overflow::Wrap);
+        assert!(!inputs.0[2].signed);
+        let inputs: Inputs = syn::parse_str("pub X, Q6.2, overflow = wrap").unwrap();
+        assert_eq!(inputs.0.len(), 1);
+        assert!(syn::parse_str::<Inputs>("A: Q1.15; B: Q2.30").is_err());
+        assert!(syn::parse_str::<Inputs>("A: Q1.15; B, Q2.30").is_err());
+    }
+
+    #[test]
+    fn test_is_lossless_into() {
+        let format = |s| syn::parse_str::<Format>(s).unwrap();
+        assert!(format("Q1.15").is_lossless_into(format("Q8.24")));
+        assert!(!format("Q8.24").is_lossless_into(format("Q1.15")));
+        assert!(format("UQ4.2").is_lossless_into(format("Q5.2")));
+        assert!(!format("UQ4.2").is_lossless_into(format("Q4.2")));
+        assert!(!format("Q4.2").is_lossless_into(format("UQ8.2")));
+    }
+
+    #[test]
+    fn test_parse_literal() {
+        let input: LiteralInput = syn::parse_str("Coeff, -0.707_1").unwrap();
+        assert_eq!(input.value, "-0.7071");
+        let input: LiteralInput = syn::parse_str("crate::Coeff, 0x10").unwrap();
+        assert_eq!(input.value, "16");
+        let input: LiteralInput = syn::parse_str("Coeff, 25e-3,").unwrap();
+        assert_eq!(input.value, "25e-3");
+        let input: LiteralInput = syn::parse_str(r#"Coeff, "0x1.8p-1""#).unwrap();
+        assert_eq!(input.value, "0x1.8p-1");
+        assert!(syn::parse_str::<LiteralInput>("Coeff, 1.5f32").is_err());
+        assert!(syn::parse_str::<LiteralInput>("Coeff, true").is_err());
+    }
+}
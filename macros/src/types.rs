@@ -11,6 +11,16 @@ pub fn unsigned_int_qualified(bits: u8) -> syn::Result<Type> {
     core_primitive_type(&unsigned_integer_type_string(bits)?)
 }
 
+// Signed integer qualified type, twice as wide as the `bits`-bit storage type
+pub fn signed_wide_int_qualified(bits: u8) -> syn::Result<Type> {
+    core_primitive_type(&wide_integer_type_string(bits, 'i')?)
+}
+
+// Unsigned integer qualified type, twice as wide as the `bits`-bit storage type
+pub fn unsigned_wide_int_qualified(bits: u8) -> syn::Result<Type> {
+    core_primitive_type(&wide_integer_type_string(bits, 'u')?)
+}
+
 /// Adds prefix to make a hygienic type name.
 pub fn core_primitive_type(s: &str) -> syn::Result<Type> {
     syn::parse_str(&format!("::core::primitive::{s}"))
@@ -34,7 +44,20 @@ fn integer_type_string(bits: u8, prefix: char) -> syn::Result<String> {
         Some(bits) => Ok(format!("{prefix}{bits}")),
         None => Err(syn::Error::new_spanned(
             bits.to_string(),
-            format!("{prefix}64 is the largest supported type"),
+            format!("{prefix}128 is the largest supported type"),
+        )),
+    }
+}
+
+/// Returns the integer type twice as wide as the narrowest integer type that
+/// can hold `bits` bits. This is used for intermediate results, so it fails
+/// for 128-bit types, which have no wider primitive type.
+fn wide_integer_type_string(bits: u8, prefix: char) -> syn::Result<String> {
+    match power_of_two_bit_length(bits) {
+        Some(n) if n <= 64 => integer_type_string(2 * n, prefix),
+        _ => Err(syn::Error::new_spanned(
+            bits.to_string(),
+            format!("{prefix}128 has no wider primitive type"),
         )),
     }
 }
@@ -76,5 +99,20 @@ mod tests {
         assert_eq!(f(15, 'u').unwrap(), "u16");
         assert_eq!(f(16, 'u').unwrap(), "u16");
         assert_eq!(f(17, 'u').unwrap(), "u32");
+        assert_eq!(f(65, 'i').unwrap(), "i128");
+        assert!(f(129, 'u').is_err());
+    }
+
+    #[test]
+    fn test_wide_integer_type_string() {
+        use super::wide_integer_type_string as f;
+        assert_eq!(f(5, 'i').unwrap(), "i16");
+        assert_eq!(f(8, 'i').unwrap(), "i16");
+        assert_eq!(f(9, 'u').unwrap(), "u32");
+        assert_eq!(f(32, 'i').unwrap(), "i64");
+        assert_eq!(f(33, 'u').unwrap(), "u128");
+        assert_eq!(f(64, 'i').unwrap(), "i128");
+        assert!(f(65, 'i').is_err());
+        assert!(f(129, 'i').is_err());
     }
 }
@@ -0,0 +1,69 @@
+//! Checks that the code generated by `q-num` builds in a `#![no_std]` crate
+//! without `alloc`.
+
+#![no_std]
+
+use q_num::{define_q_num, q_num, Error, OverflowMode, QNum, RoundingMode};
+
+define_q_num! {
+    pub Sample: Q1.15;
+    pub Coeff: Q2.30, overflow = saturate;
+    pub Acc: UQ16.48, overflow = panic;
+    pub Precise: Q64.192, overflow = saturate;
+}
+
+define_q_num!(pub Gain, Q6.2 * Q4.4, overflow = wrap, rounding = nearest_even);
+
+/// An angle, in degrees.
+#[q_num(Q10.6)]
+pub struct Angle;
+
+pub fn scale(sample: Sample, gain: Gain) -> Result<Gain, Error> {
+    let sample: Gain = sample.convert_with(RoundingMode::Floor, OverflowMode::Saturate);
+    let product = sample * gain;
+    Gain::from_f64_round(f64::from(product), RoundingMode::Ceil)
+}
+
+pub fn mix(a: Sample, b: Coeff) -> Coeff {
+    Coeff::from(a) + b
+}
+
+pub fn accumulate(values: &[Acc]) -> Option<Acc> {
+    values
+        .iter()
+        .try_fold(Acc::try_from(0).ok()?, |sum, &value| sum.checked_add(value))
+}
+
+pub fn square(value: Precise) -> Precise {
+    value * value
+}
+
+pub fn wrap(value: f64) -> Angle {
+    Angle::from_f64_wrapping(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_std() {
+        let half = Sample::try_from(0.5).unwrap();
+        assert_eq!(f64::from(mix(half, Coeff::try_from(1.0).unwrap())), 1.5);
+        assert_eq!(
+            Sample::try_from(1.0),
+            Err(Error::OutOfRange {
+                value: 1.0,
+                min: -1.0,
+                max: 1.0 - 1.0 / 32768.0
+            })
+        );
+        let gain = Gain::try_from(3.0).unwrap();
+        assert_eq!(f64::from(scale(half, gain).unwrap()), 1.5);
+        let acc = Acc::try_from(2.5).unwrap();
+        assert_eq!(accumulate(&[acc, acc]), Acc::try_from(5.0).ok());
+        assert_eq!(f64::from(wrap(520.0)), -504.0);
+        let precise = Precise::try_from(-1.5).unwrap();
+        assert_eq!(square(precise), Precise::try_from(2.25).unwrap());
+    }
+}
@@ -0,0 +1,40 @@
+use core::fmt;
+
+/// The error returned by the fallible conversions of the types defined with
+/// `define_q_num!`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value, after rounding, is outside the range `min..=max` of the
+    /// target format.
+    OutOfRange {
+        /// The value being converted.
+        value: f64,
+        /// The smallest value of the target format.
+        min: f64,
+        /// The largest value of the target format.
+        max: f64,
+    },
+    /// The value is NaN.
+    NotANumber,
+    /// The value does not fit in the target format. This is returned by
+    /// conversions from the integers and between Q formats, and by parsing.
+    Overflow,
+    /// The string is not a number.
+    InvalidSyntax,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange { value, min, max } => {
+                write!(f, "{value} is out of range ({min} to {max})")
+            }
+            Error::NotANumber => f.write_str("NaN cannot be converted"),
+            Error::Overflow => f.write_str("value is out of range"),
+            Error::InvalidSyntax => f.write_str("invalid number syntax"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,383 @@
+use crate::limbs::{self, MAX_LIMBS};
+use crate::QNum;
+use core::fmt::{self, Alignment, Formatter, Write};
+
+/// The most digits of a value with up to 255 integer bits and 255 fractional
+/// bits: 77 integer digits, up to 255 fractional digits and one for a carry.
+const MAX_DIGITS: usize = 77 + 255 + 1;
+
+/// The exact decimal representation of a magnitude, as ASCII digits.
+struct Decimal {
+    digits: [u8; MAX_DIGITS],
+    len: usize,
+    /// The number of integer digits, i.e. the position of the decimal point.
+    point: usize,
+}
+
+impl Decimal {
+    /// Returns the digits of `magnitude / 2^frac_bits`, where
+    /// `frac_bits <= 127`. There is at least one integer digit, and no trailing
+    /// zeros after the decimal point.
+    fn new(magnitude: u128, frac_bits: u8) -> Self {
+        Self::from_limbs(&[magnitude as u64, (magnitude >> 64) as u64], frac_bits)
+    }
+
+    /// Like `new`, for a magnitude given as little-endian limbs (at most
+    /// `MAX_LIMBS`), with an integer part below 2^255.
+    fn from_limbs(magnitude: &[u64], frac_bits: u8) -> Self {
+        let mut decimal = Decimal {
+            digits: [b'0'; MAX_DIGITS],
+            len: 0,
+            point: 0,
+        };
+        let mut int = [0; MAX_LIMBS];
+        limbs::shr(magnitude, frac_bits as u32, &mut int);
+        loop {
+            decimal.digits[decimal.len] = b'0' + limbs::div_rem_small(&mut int, 10) as u8;
+            decimal.len += 1;
+            if int.iter().all(|&limb| limb == 0) {
+                break;
+            }
+        }
+        decimal.digits[..decimal.len].reverse();
+        decimal.point = decimal.len;
+        // The fractional part is below 2^255, so `frac * 10` still fits.
+        let mut frac = [0; MAX_LIMBS];
+        frac[..magnitude.len()].copy_from_slice(magnitude);
+        limbs::truncate(&mut frac, frac_bits as u32);
+        while frac.iter().any(|&limb| limb != 0) {
+            limbs::mul_small(&mut frac, 10);
+            let mut digit = [0];
+            limbs::shr(&frac, frac_bits as u32, &mut digit);
+            decimal.digits[decimal.len] = b'0' + digit[0] as u8;
+            decimal.len += 1;
+            limbs::truncate(&mut frac, frac_bits as u32);
+        }
+        decimal
+    }
+
+    /// Rounds to the first `keep` digits (where `keep < len`), with ties to
+    /// even. A carry out of the first digit adds a digit in front.
+    fn round_to(&mut self, keep: usize) {
+        let rest = &self.digits[keep..self.len];
+        let up = match rest[0] {
+            b'6'..=b'9' => true,
+            b'5' => {
+                rest[1..].iter().any(|&d| d != b'0')
+                    || (keep > 0 && (self.digits[keep - 1] - b'0') % 2 == 1)
+            }
+            _ => false,
+        };
+        self.len = keep;
+        if !up {
+            return;
+        }
+        for digit in self.digits[..keep].iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                return;
+            }
+        }
+        self.digits.copy_within(0..keep, 1);
+        self.digits[0] = b'1';
+        self.len += 1;
+        self.point += 1;
+    }
+
+    fn str(&self, start: usize, end: usize) -> Result<&str, fmt::Error> {
+        core::str::from_utf8(&self.digits[start..end]).map_err(|_| fmt::Error)
+    }
+}
+
+/// Writes `len` characters with `write` (after the sign, which is written
+/// first), padded according to the width, fill and alignment of `f`.
+fn pad(
+    f: &mut Formatter<'_>,
+    negative: bool,
+    len: usize,
+    write: impl FnOnce(&mut Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    let sign = if negative {
+        "-"
+    } else if f.sign_plus() {
+        "+"
+    } else {
+        ""
+    };
+    let len = len + sign.len();
+    let padding = f.width().map_or(0, |width| width.saturating_sub(len));
+    if f.sign_aware_zero_pad() {
+        f.write_str(sign)?;
+        write_repeated(f, '0', padding)?;
+        return write(f);
+    }
+    let (before, after) = match f.align() {
+        Some(Alignment::Left) => (0, padding),
+        Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+        Some(Alignment::Right) | None => (padding, 0),
+    };
+    let fill = f.fill();
+    write_repeated(f, fill, before)?;
+    f.write_str(sign)?;
+    write(f)?;
+    write_repeated(f, fill, after)
+}
+
+fn write_repeated(f: &mut Formatter<'_>, c: char, count: usize) -> fmt::Result {
+    for _ in 0..count {
+        f.write_char(c)?;
+    }
+    Ok(())
+}
+
+/// Formats `value` exactly in decimal, like `Display` for `f64`: with as many
+/// fractional digits as needed, or rounded (with ties to even) to the
+/// precision of `f`, if given.
+pub fn display<T: QNum>(value: T, f: &mut Formatter<'_>) -> fmt::Result {
+    let (negative, magnitude) = value.to_parts();
+    write_display(negative, Decimal::new(magnitude, T::FRAC_BITS), f)
+}
+
+fn write_display(negative: bool, mut decimal: Decimal, f: &mut Formatter<'_>) -> fmt::Result {
+    let frac_digits = decimal.len - decimal.point;
+    let (shown, zeros) = match f.precision() {
+        Some(precision) if precision < frac_digits => {
+            decimal.round_to(decimal.point + precision);
+            (precision, 0)
+        }
+        Some(precision) => (frac_digits, precision - frac_digits),
+        None => (frac_digits, 0),
+    };
+    let has_point = shown + zeros > 0;
+    let len = decimal.point + has_point as usize + shown + zeros;
+    pad(f, negative, len, |f| {
+        f.write_str(decimal.str(0, decimal.point)?)?;
+        if has_point {
+            f.write_char('.')?;
+            f.write_str(decimal.str(decimal.point, decimal.len)?)?;
+            write_repeated(f, '0', zeros)?;
+        }
+        Ok(())
+    })
+}
+
+/// Formats `value` exactly in scientific notation, like `LowerExp` for `f64`,
+/// e.g. `1.5e-3`, rounding (with ties to even) to the precision of `f`, if
+/// given.
+pub fn lower_exp<T: QNum>(value: T, f: &mut Formatter<'_>) -> fmt::Result {
+    let (negative, magnitude) = value.to_parts();
+    write_lower_exp(negative, Decimal::new(magnitude, T::FRAC_BITS), f)
+}
+
+fn write_lower_exp(negative: bool, mut decimal: Decimal, f: &mut Formatter<'_>) -> fmt::Result {
+    let first = |decimal: &Decimal| {
+        decimal.digits[..decimal.len]
+            .iter()
+            .position(|&d| d != b'0')
+            .unwrap_or(decimal.len - 1)
+    };
+    let mut start = first(&decimal);
+    if let Some(precision) = f.precision() {
+        if start + 1 + precision < decimal.len {
+            decimal.round_to(start + 1 + precision);
+            start = first(&decimal);
+        }
+    }
+    // Without a precision, drop the trailing zeros (of the integer part).
+    let end = match f.precision() {
+        Some(precision) => decimal.len.min(start + 1 + precision),
+        None => {
+            let mut end = decimal.len;
+            while end > start + 1 && decimal.digits[end - 1] == b'0' {
+                end -= 1;
+            }
+            end
+        }
+    };
+    let shown = end - start - 1;
+    let zeros = f.precision().map_or(0, |precision| precision - shown);
+    let exponent = decimal.point as isize - start as isize - 1;
+    let mut exponent_len = if exponent < 0 { 2 } else { 1 };
+    let mut n = exponent.unsigned_abs();
+    while n >= 10 {
+        exponent_len += 1;
+        n /= 10;
+    }
+    let has_point = shown + zeros > 0;
+    let len = 1 + has_point as usize + shown + zeros + 1 + exponent_len;
+    pad(f, negative, len, |f| {
+        f.write_str(decimal.str(start, start + 1)?)?;
+        if has_point {
+            f.write_char('.')?;
+            f.write_str(decimal.str(start + 1, end)?)?;
+            write_repeated(f, '0', zeros)?;
+        }
+        write!(f, "e{exponent}")
+    })
+}
+
+/// Formats `value` as its type name, its exact value and its format, e.g.
+/// `X(-1.25, Q6.2)`.
+pub fn debug<T: QNum>(value: T, name: &str, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{name}(")?;
+    display(value, f)?;
+    write!(f, ", {})", T::Q_NOTATION)
+}
+
+/// Returns the sign and the digits of an inner value stored in limbs (see
+/// `limbs`).
+fn limbs_decimal<const N: usize>(
+    bits: [u64; N],
+    signed: bool,
+    frac_bits: u8,
+    pad_bits: u8,
+) -> (bool, Decimal) {
+    let magnitude = limbs::magnitude(bits, signed);
+    let mut unpadded = [0; N];
+    limbs::shr(&magnitude, pad_bits as u32, &mut unpadded);
+    let negative = limbs::is_negative(&bits, signed);
+    (negative, Decimal::from_limbs(&unpadded, frac_bits))
+}
+
+/// Like `display`, for a format wider than 128 bits, whose inner value is
+/// stored in limbs.
+pub fn display_limbs<const N: usize>(
+    bits: [u64; N],
+    signed: bool,
+    frac_bits: u8,
+    pad_bits: u8,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let (negative, decimal) = limbs_decimal(bits, signed, frac_bits, pad_bits);
+    write_display(negative, decimal, f)
+}
+
+/// Like `lower_exp`, for a format wider than 128 bits, whose inner value is
+/// stored in limbs.
+pub fn lower_exp_limbs<const N: usize>(
+    bits: [u64; N],
+    signed: bool,
+    frac_bits: u8,
+    pad_bits: u8,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let (negative, decimal) = limbs_decimal(bits, signed, frac_bits, pad_bits);
+    write_lower_exp(negative, decimal, f)
+}
+
+/// Like `debug`, for a format wider than 128 bits, whose inner value is stored
+/// in limbs.
+pub fn debug_limbs<const N: usize>(
+    bits: [u64; N],
+    (signed, frac_bits, pad_bits): (bool, u8, u8),
+    name: &str,
+    q_notation: &str,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    write!(f, "{name}(")?;
+    display_limbs(bits, signed, frac_bits, pad_bits, f)?;
+    write!(f, ", {q_notation})")
+}
+
+/// Formats the bits of a value as its layout, e.g. `1|011.01__` for the
+/// `Q4.2` value -4.75 stored in an `i8`: the sign bit (if signed) followed by
+/// `|`, the other integer bits, a point, the fractional bits, and an
+/// underscore per padding bit. `bits` holds the inner value in its low
+/// `total_bits` bits. If `right_aligned` is true, the padding comes first, e.g.
+/// `__1|011.01`.
+pub fn binary_layout(
+    bits: u128,
+    total_bits: u8,
+    int_bits: u8,
+    frac_bits: u8,
+    signed: bool,
+    right_aligned: bool,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    // Up to 128 bits, a separator and a point.
+    let mut buffer = [0u8; 130];
+    let mut len = 0;
+    let used_bits = int_bits + frac_bits;
+    // The position of the first used bit, counting from the most significant.
+    let start = if right_aligned {
+        total_bits - used_bits
+    } else {
+        0
+    };
+    for i in 0..total_bits {
+        if i == start + int_bits {
+            buffer[len] = b'.';
+            len += 1;
+        }
+        let bit = bits >> (total_bits - 1 - i) & 1;
+        buffer[len] = if i < start || i >= start + used_bits {
+            b'_'
+        } else {
+            b'0' + bit as u8
+        };
+        len += 1;
+        if i == start && signed {
+            buffer[len] = b'|';
+            len += 1;
+        }
+    }
+    let layout = core::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?;
+    pad(f, false, len, |f| f.write_str(layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Decimal;
+
+    fn check(decimal: &Decimal, digits: &str, point: usize) {
+        assert_eq!(&decimal.digits[..decimal.len], digits.as_bytes());
+        assert_eq!(decimal.point, point);
+    }
+
+    #[test]
+    fn test_decimal() {
+        check(&Decimal::new(0, 2), "0", 1);
+        check(&Decimal::new(0b10111, 2), "575", 1);
+        check(&Decimal::new(1, 8), "000390625", 1);
+        check(
+            &Decimal::new(u128::MAX, 0),
+            "340282366920938463463374607431768211455",
+            39,
+        );
+        // 2^-127 has 127 fractional digits, ending in 5.
+        let decimal = Decimal::new(1, 127);
+        assert_eq!((decimal.len, decimal.digits[127]), (128, b'5'));
+        // Just below 2.
+        let decimal = Decimal::new(u128::MAX, 127);
+        assert_eq!(&decimal.digits[..4], b"1999");
+        // 2^255 - 2^-255, the largest magnitude of a `UQ255.255`.
+        let mut magnitude = [u64::MAX; 8];
+        magnitude[7] >>= 2;
+        let decimal = Decimal::from_limbs(&magnitude, 255);
+        assert_eq!((decimal.point, decimal.len), (77, 77 + 255));
+        assert_eq!(&decimal.digits[..5], b"57896");
+    }
+
+    #[test]
+    fn test_round_to() {
+        let round = |magnitude, frac_bits, keep, digits, point| {
+            let mut decimal = Decimal::new(magnitude, frac_bits);
+            decimal.round_to(keep);
+            check(&decimal, digits, point);
+        };
+        // 0.625 and 0.375
+        round(0b101, 3, 3, "062", 1);
+        round(0b011, 3, 3, "038", 1);
+        // 9.75
+        round(0b100111, 2, 2, "98", 1);
+        round(0b100111, 2, 1, "10", 2);
+        // 0.5 and 1.5
+        round(0b1, 1, 1, "0", 1);
+        round(0b11, 1, 1, "2", 1);
+        // 0.09375
+        round(0b11, 5, 3, "009", 1);
+        round(0b11, 5, 2, "01", 1);
+    }
+}
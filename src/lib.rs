@@ -13,8 +13,9 @@
 //! - **n** is the number of fractional bits.
 //!
 //! Internally, the macro chooses the narrowest primitive integer type that can
-//! hold `m + n` bits, up to `u64` (unsigned) and `i64` (signed). More internal
-//! details are discussed below.
+//! hold `m + n` bits, up to `u128` (unsigned) and `i128` (signed). Wider formats
+//! are stored in an array of `u64` limbs. More internal details are discussed
+//! below.
 //!
 //! ## Q Number Value
 //!
@@ -51,8 +52,33 @@
 //!
 //! The example above also defines the following floating-point conversions:
 //!
-//! - `MyQ::from(f64) -> MyQ`
+//! - `MyQ::try_from(f64) -> Result<MyQ, q_num::Error>`
 //! - `f64::from(MyQ) -> f64`
+//! - `MyQ::try_from(f32) -> Result<MyQ, q_num::Error>`
+//! - `MyQ.to_f32() -> f32`, which loses precision if `USED_BITS > 24`
+//! - `f32::from(MyQ) -> f32`, only if `USED_BITS <= 24` (so it is lossless)
+//!
+//! Integers convert into the integer bits: via `From` for the primitive
+//! integer types whose every value fits, and via `TryFrom` for the others. In
+//! the other direction, there are `to_int_trunc`, `to_int_floor` and
+//! `to_int_round` (returning the inner type), plus `int_part` and `frac_part`.
+//!
+//! Every defined type implements [`QNum`], which converts between any two Q
+//! formats without a round trip through `f64`: `convert` (only compiles if
+//! lossless), `try_convert` and `convert_with` (with an explicit
+//! [`RoundingMode`] and [`OverflowMode`]).
+//!
+//! `QNum::widening_mul` multiplies values of two (possibly different) formats
+//! exactly. The format of the product can be declared as a product of formats:
+//!
+//! ```
+//! # use q_num::{define_q_num, QNum};
+//! define_q_num!(A, Q6.2);
+//! define_q_num!(B, Q4.4);
+//! define_q_num!(P, Q6.2 * Q4.4); // i.e. Q10.6
+//! let p: P = A::try_from(-1.25).unwrap().widening_mul(B::try_from(0.0625).unwrap());
+//! assert_eq!(f64::from(p), -0.078125);
+//! ```
 //!
 //! It also defines the following getter and setter to access the internal
 //! representation:
@@ -60,9 +86,154 @@
 //! - `MyQ.to_bits() -> i8`
 //! - `MyQ::from_bits(i8) -> MyQ`
 //!
+//! The fallible conversions return an [`Error`], which can be matched on:
+//!
+//! ```
+//! # use q_num::{define_q_num, Error};
+//! define_q_num!(X, Q6.2);
+//! assert_eq!(X::try_from(f64::NAN), Err(Error::NotANumber));
+//! assert!(matches!(X::try_from(40.0), Err(Error::OutOfRange { .. })));
+//! assert_eq!(X::try_from(40), Err(Error::Overflow));
+//! ```
+//!
+//! ### Formatting
+//!
+//! `Display` prints the exact decimal value, without a round trip through
+//! `f64`. It honors the precision (rounding with ties to even), width, fill,
+//! alignment, `+` and `0` flags. `LowerExp` does the same in scientific
+//! notation, and `Debug` also shows the format:
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(X, Q6.2);
+//! let a = X::try_from(-30.25).unwrap();
+//! assert_eq!(a.to_string(), "-30.25");
+//! assert_eq!(format!("{a:.1}"), "-30.2");
+//! assert_eq!(format!("{a:>8}"), "  -30.25");
+//! assert_eq!(format!("{a:e}"), "-3.025e1");
+//! assert_eq!(format!("{a:?}"), "X(-30.25, Q6.2)");
+//! ```
+//!
+//! `Binary`, `Octal`, `LowerHex` and `UpperHex` format the inner value,
+//! padding included. The alternate binary form, `{:#b}`, shows the layout
+//! instead: the sign bit (if any) and `|`, the integer bits, a point, the
+//! fractional bits and an underscore per padding bit:
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(U, UQ4.2);
+//! define_q_num!(X, Q4.2);
+//! let u = U::try_from(11.25).unwrap();
+//! assert_eq!(format!("{u:b} {u:#x}"), "10110100 0xb4");
+//! assert_eq!(format!("{u:#b}"), "1011.01__");
+//! assert_eq!(format!("{:#b}", X::try_from(-4.75).unwrap()), "1|011.01__");
+//! ```
+//!
+//! ### Parsing
+//!
+//! `FromStr` parses decimal numbers with an optional exponent (`-1.25`,
+//! `25e-3`), and binary, octal or hexadecimal numbers with an optional binary
+//! exponent (`0b1.01`, `0x1.8p3`). It rounds the exact value once, with the
+//! `ROUNDING` mode, so no precision is lost to an intermediate `f64`:
+//!
+//! ```
+//! # use q_num::{define_q_num, Error};
+//! define_q_num!(Coeff, Q1.15, rounding = nearest_even);
+//! let c: Coeff = "0.70710678".parse().unwrap();
+//! assert_eq!(c.to_bits(), 0x5a82);
+//! assert_eq!("0x1.8p-1".parse::<Coeff>().unwrap().to_string(), "0.75");
+//! assert_eq!("1.5".parse::<Coeff>(), Err(Error::Overflow));
+//! ```
+//!
+//! ### Constants
+//!
+//! `to_bits`, `from_bits`, `from_f64_round` and `from_str_const` (which parses
+//! like `FromStr`) are `const fn`. The `q!` macro builds a value from a number
+//! (or a string, for other syntaxes) at compile time, so that lookup tables and
+//! filter coefficients can be `const` or `static` items:
+//!
+//! ```
+//! # use q_num::{define_q_num, q};
+//! define_q_num!(Coeff, Q1.15, rounding = nearest_even);
+//! const TAPS: [Coeff; 3] = [q!(Coeff, 0.7071), q!(Coeff, -0.5), q!(Coeff, "0x1p-3")];
+//! assert_eq!(TAPS[0].to_bits(), 0x5a82);
+//! ```
+//!
+//! A proc macro only sees the type's name, not the format it was defined with,
+//! so `q!` does not compute the bits itself. It expands to an inline `const`
+//! block that calls `from_str_const`, which parses the number with a single
+//! exact rounding and checks its range during constant evaluation. It also
+//! works with `Self` inside an `impl`. A number that is out of range is still a
+//! compile error:
+//!
+//! ```compile_fail
+//! # use q_num::{define_q_num, q};
+//! define_q_num!(Coeff, Q1.15);
+//! const ONE: Coeff = q!(Coeff, 1.0);
+//! ```
+//!
+//! ### Rounding
+//!
+//! `MyQ::from_f64_round(f64, RoundingMode)` converts with an explicit
+//! [`RoundingMode`]. `TryFrom<f64>` rounds toward zero unless the `rounding`
+//! option selects a different default:
+//!
+//! - `rounding = nearest_even`
+//! - `rounding = nearest_away`
+//! - `rounding = floor`
+//! - `rounding = ceil`
+//! - `rounding = toward_zero` (the default)
+//!
+//! ```
+//! # use q_num::{define_q_num, RoundingMode};
+//! define_q_num!(X, Q6.2, rounding = nearest_even);
+//! assert_eq!(X::ROUNDING, RoundingMode::NearestTiesEven);
+//! let a = X::from_f64_round(0.3, RoundingMode::Ceil).unwrap();
+//! assert_eq!(f64::from(a), 0.5);
+//! let b = X::try_from(0.3).unwrap();
+//! assert_eq!(f64::from(b), 0.25);
+//! ```
+//!
+//! To clamp rather than fail, use one of the infallible conversions, which also
+//! use the `ROUNDING` mode:
+//!
+//! - `MyQ::from_f64_saturating(f64)` saturates at the numeric bounds
+//!   (panics on NaN)
+//! - `MyQ::from_f64_wrapping(f64)` wraps around at the numeric bounds (panics
+//!   on NaN and the infinities)
+//! - `MyQ::from_f64_lossy(f64)` saturates at the numeric bounds and converts
+//!   NaN to zero
+//!
+//! ## Also Defined: Operators
+//!
+//! The arithmetic operators `+`, `-`, `*`, `/`, `%`, unary `-` (signed types
+//! only), `<<` and `>>` (by `u32`), and their compound assignment forms are
+//! implemented. Like the primitive integers, overflow panics in debug builds.
+//!
+//! - Multiplication rounds toward negative infinity.
+//! - Division rounds toward zero.
+//!
+//! To choose the overflow behavior explicitly, use the same method families
+//! that the primitive integers have: `checked_*`, `wrapping_*`, `saturating_*`
+//! and `overflowing_*` for `add`, `sub`, `mul`, `div`, `neg` and (signed types
+//! only) `abs`.
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(X, Q4.2);
+//! let a = X::try_from(7.5).unwrap();
+//! assert_eq!(a.checked_add(a), None);
+//! assert_eq!(a.saturating_add(a), X::MAX);
+//! ```
+//!
+//! The types also derive `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash`,
+//! so they can be sorted and used as `BTreeMap` or `HashMap` keys. Values
+//! compare by their numeric value.
+//!
 //! ## Macro Variations
 //!
-//! Variations include (a) signed vs. unsigned, and (b) visibility.
+//! Variations include (a) signed vs. unsigned, (b) visibility, (c) attributes,
+//! (d) the attribute form, and (e) multiple types.
 //!
 //! ### Unsigned Variation
 //!
@@ -77,13 +248,118 @@
 //!
 //! ### Visibility
 //!
-//! A caller can optionally include a visibility modifier for the new type:
+//! A caller can optionally include a visibility modifier for the new type,
+//! which also applies to its associated constants and methods. Without one,
+//! they are private, like any other item.
 //!
 //! ```
 //! # use q_num::define_q_num;
 //! define_q_num!(pub MyNum, UQ11.5);
+//! define_q_num!(pub(crate) MyOtherNum, UQ11.5);
 //! ```
 //!
+//! ### Attributes
+//!
+//! Outer attributes and doc comments before the name are forwarded to the
+//! struct. A `#[cfg(...)]` also applies to the generated impls. The struct
+//! always gets a generated doc comment describing its format, range and
+//! resolution, which follows any doc comment given by the caller.
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(
+//!     /// The gain of an amplifier stage.
+//!     #[derive(Default)]
+//!     #[repr(transparent)]
+//!     pub Gain, Q2.14
+//! );
+//! assert_eq!(Gain::default(), Gain::from_bits(0));
+//! ```
+//!
+//! ### Attribute Form
+//!
+//! The `#[q_num(...)]` attribute takes the same format and options, and
+//! generates the type from a unit struct declared by the caller, keeping its
+//! name, visibility and attributes:
+//!
+//! ```
+//! # use q_num::q_num;
+//! /// An angle, in degrees.
+//! #[q_num(Q10.6, overflow = wrap)]
+//! pub struct Angle;
+//!
+//! let a = Angle::try_from(270.5).unwrap();
+//! assert_eq!(f64::from(a + a), -483.0);
+//! ```
+//!
+//! The struct may instead be a tuple struct declaring the inner type, such as
+//! `pub struct Angle(i16);`, to make the storage explicit. Any other field is
+//! an error that names the expected type.
+//!
+//! ### Multiple Types
+//!
+//! Several types can be defined in one invocation, each terminated by a
+//! semicolon. Between any two of them, a `From` conversion is generated
+//! wherever it is lossless, so mixed-format arithmetic is one `into()` away:
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num! {
+//!     pub Sample: Q1.15;
+//!     pub Coeff: Q2.30, overflow = saturate;
+//!     Acc: Q16.48;
+//! }
+//! let a = Sample::try_from(0.5).unwrap();
+//! let b = Coeff::try_from(1.25).unwrap();
+//! assert_eq!(f64::from(Acc::from(a) + Acc::from(b)), 1.75);
+//! ```
+//!
+//! ### Overflow Behavior
+//!
+//! By default, the operators `+`, `-`, `*`, `/` and unary `-` behave like they
+//! do for the primitive integers: they panic on overflow in debug builds. A
+//! caller can choose a different behavior with the `overflow` option:
+//!
+//! - `overflow = wrap`: wrap around at the numeric bounds
+//! - `overflow = saturate`: saturate at the numeric bounds
+//! - `overflow = panic`: panic, even in release builds
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(Gain, Q2.14, overflow = saturate);
+//! let a = Gain::try_from(1.5).unwrap();
+//! assert_eq!(a + a, Gain::MAX);
+//! ```
+//!
+//! ### Storage Layout
+//!
+//! By default, the value is left-aligned in the inner type: any padding bits
+//! sit below it and are zero. Hardware registers often store the value in the
+//! low bits instead, which the `layout = right` option selects. The padding
+//! then sits above the value and is sign-extended (zero for unsigned types),
+//! so `to_bits` and `from_bits` match the register directly. Arithmetic is
+//! unaffected by the layout.
+//!
+//! Every type can also convert to and from either layout with `to_left_bits`,
+//! `from_left_bits`, `to_right_bits` and `from_right_bits`:
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(Reg, UQ4.2, layout = right);
+//! let a = Reg::from_bits(0b10_1101);
+//! assert_eq!(f64::from(a), 11.25);
+//! assert_eq!(format!("{a:#b}"), "__1011.01");
+//! assert_eq!(a.to_left_bits(), 0b1011_0100);
+//! assert_eq!(a.wrapping_add(a).to_bits(), 0b01_1010); // wraps at 6 bits
+//! ```
+//!
+//! The option is not supported for formats wider than 128 bits.
+//!
+//! ## `no_std`
+//!
+//! This crate is `#![no_std]`, and the generated code refers only to `::core`
+//! and `::q_num`, so the macros work in crates without `std` or `alloc`.
+//!
 //! ## Numerical Properties
 //!
 //! The value of a Q number is the ratio of the storage number and a fixed
@@ -101,6 +377,48 @@
 //! complement representation, meaning that the sign bit is already counted
 //! towards the integer part.)
 //!
+//! The `storage` option overrides the inner type, for example to match the
+//! alignment of neighboring fields or an FFI declaration. It must have the
+//! signedness of the format and hold its used bits; the rest is padding:
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(X, Q4.2, storage = i32);
+//! assert_eq!((X::TOTAL_BITS, X::PAD_BITS), (32, 26));
+//! assert_eq!(X::try_from(1.25).unwrap().to_bits(), 5 << 26);
+//! ```
+//!
+//! Multiplication and division rescale through an intermediate twice as wide
+//! as the inner type. For 128-bit formats, which have no wider primitive type,
+//! the intermediate is a 256-bit pair of `u128`s:
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(X, Q32.64); // stored in an `i128`
+//! let a = X::try_from(-1.5).unwrap();
+//! assert_eq!((a * a).to_string(), "2.25");
+//! assert_eq!((X::from(1i8) / X::from(4i8)).to_string(), "0.25");
+//! ```
+//!
+//! Formats of more than 128 bits, such as `Q64.192`, wrap a `[u64; N]` array
+//! of little-endian limbs instead. These support fewer operations: `+`, `-`,
+//! `*` and unary `-` (with their method families), comparison, `Display`,
+//! `LowerExp`, `Debug`, parsing (so `q!` works too), `TryFrom<f64>`,
+//! `TryFrom<f32>` and `From` for `f64`. They lack `/`, the shifts, the integer
+//! conversions and integer-part methods, `to_f32`, the saturating, wrapping and
+//! lossy `f64` constructors, the binary and hexadecimal formats and
+//! `layout = right`, and do not implement [`QNum`]. Their `TOTAL_BITS` and
+//! `USED_BITS` are `u16`.
+//!
+//! ```
+//! # use q_num::define_q_num;
+//! define_q_num!(X, Q64.192); // stored in a `[u64; 4]`
+//! let a = X::try_from(-1.5).unwrap();
+//! assert_eq!((a * a - a).to_string(), "3.75");
+//! assert!(a < X::try_from(0.25).unwrap());
+//! assert_eq!("-1.5".parse(), Ok(a));
+//! ```
+//!
 //! [newtype pattern]:
 //!     https://doc.rust-lang.org/rust-by-example/generics/new_types.html
 //!
@@ -108,22 +426,33 @@
 //!
 //! https://en.wikipedia.org/wiki/Q_(number_format)
 
-mod gen;
-mod literal;
-mod math;
+#![no_std]
+
+pub use q_num_macros::{define_q_num, q, q_num};
+
+mod error;
+mod format;
+mod limbs;
+mod overflow;
 mod parse;
-mod types;
+mod qnum;
+mod rounding;
+mod wide;
 
-use crate::gen::generate;
-use crate::parse::Input;
-use proc_macro::TokenStream;
-use syn::parse_macro_input;
+pub use crate::error::Error;
+pub use crate::overflow::OverflowMode;
+pub use crate::qnum::QNum;
+pub use crate::rounding::RoundingMode;
 
-#[proc_macro]
-pub fn define_q_num(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as Input);
-    match generate(input) {
-        Ok(token_stream) => token_stream.into(),
-        Err(e) => e.to_compile_error().into(),
-    }
+/// Support for the generated code. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::format::{
+        binary_layout, debug, debug_limbs, display, display_limbs, lower_exp, lower_exp_limbs,
+    };
+    pub use crate::limbs::{
+        limbs_add, limbs_cmp, limbs_from_f64, limbs_from_parts, limbs_mul, limbs_sub, limbs_to_f64,
+    };
+    pub use crate::parse::{from_str, parse, parse_limbs};
+    pub use crate::wide::{div_i128, div_u128, mul_i128, mul_u128};
 }
@@ -0,0 +1,379 @@
+//! Arithmetic on the `[u64; N]` limbs of formats wider than 128 bits. The
+//! limbs are little-endian (the first one is the least significant), and hold
+//! the left-aligned inner value in two's complement for signed formats.
+
+use core::cmp::Ordering;
+
+/// The most limbs a format can need: `Q255.255` has 510 bits.
+pub(crate) const MAX_LIMBS: usize = 8;
+
+pub(crate) fn is_negative(limbs: &[u64], signed: bool) -> bool {
+    signed && limbs[limbs.len() - 1] >> 63 == 1
+}
+
+pub(crate) const fn is_zero(limbs: &[u64]) -> bool {
+    let mut i = 0;
+    while i < limbs.len() {
+        if limbs[i] != 0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Adds `b` and `carry` to `a`, returning the carry out. `b` may be shorter
+/// than `a`.
+fn add_in_place(a: &mut [u64], b: &[u64], carry: bool) -> bool {
+    let mut carry = carry;
+    for (i, x) in a.iter_mut().enumerate() {
+        let (sum, c1) = x.overflowing_add(b.get(i).copied().unwrap_or(0));
+        let (sum, c2) = sum.overflowing_add(carry as u64);
+        *x = sum;
+        carry = c1 || c2;
+    }
+    carry
+}
+
+/// Negates `a` in two's complement, wrapping around.
+const fn negate(a: &mut [u64]) {
+    // Invert, then add one.
+    let mut carry = true;
+    let mut i = 0;
+    while i < a.len() {
+        (a[i], carry) = (!a[i]).overflowing_add(carry as u64);
+        i += 1;
+    }
+}
+
+/// Returns the magnitude of `a`, which is below 2^(64 * N), so it fits.
+pub(crate) fn magnitude<const N: usize>(a: [u64; N], signed: bool) -> [u64; N] {
+    let mut magnitude = a;
+    if is_negative(&a, signed) {
+        negate(&mut magnitude);
+    }
+    magnitude
+}
+
+/// Writes `src >> shift` to `dst`, which may be shorter or longer than `src`.
+pub(crate) fn shr(src: &[u64], shift: u32, dst: &mut [u64]) {
+    let (limbs, bits) = ((shift / 64) as usize, shift % 64);
+    for (i, x) in dst.iter_mut().enumerate() {
+        let low = src.get(i + limbs).copied().unwrap_or(0);
+        let high = src.get(i + limbs + 1).copied().unwrap_or(0);
+        *x = if bits == 0 {
+            low
+        } else {
+            (low >> bits) | (high << (64 - bits))
+        };
+    }
+}
+
+/// Returns whether any of the low `bits` bits of `a` are set.
+fn any_low_bits(a: &[u64], bits: u32) -> bool {
+    let (limbs, bits) = ((bits / 64) as usize, bits % 64);
+    !is_zero(&a[..limbs]) || (bits > 0 && a[limbs] & ((1 << bits) - 1) != 0)
+}
+
+/// Clears the bits of `a` at and above bit `bits`.
+pub(crate) fn truncate(a: &mut [u64], bits: u32) {
+    for (i, x) in a.iter_mut().enumerate() {
+        let start = i as u32 * 64;
+        if start >= bits {
+            *x = 0;
+        } else if bits - start < 64 {
+            *x &= (1 << (bits - start)) - 1;
+        }
+    }
+}
+
+/// Multiplies `a` by `factor`, returning the limb carried out.
+pub(crate) fn mul_small(a: &mut [u64], factor: u64) -> u64 {
+    let mut carry = 0;
+    for x in a.iter_mut() {
+        let product = *x as u128 * factor as u128 + carry as u128;
+        *x = product as u64;
+        carry = (product >> 64) as u64;
+    }
+    carry
+}
+
+/// Divides `a` by `divisor`, returning the remainder.
+pub(crate) fn div_rem_small(a: &mut [u64], divisor: u64) -> u64 {
+    let mut remainder = 0;
+    for x in a.iter_mut().rev() {
+        let dividend = ((remainder as u128) << 64) | *x as u128;
+        *x = (dividend / divisor as u128) as u64;
+        remainder = (dividend % divisor as u128) as u64;
+    }
+    remainder
+}
+
+/// Returns the inner value with the given sign and magnitude (whose limbs
+/// beyond the first `N` are the overflow), along with a boolean indicating
+/// whether it overflowed. If it did, the low `64 * N` bits are returned.
+const fn from_magnitude<const N: usize>(
+    negative: bool,
+    magnitude: &[u64],
+    signed: bool,
+) -> ([u64; N], bool) {
+    let (low, high) = magnitude.split_at(N);
+    let mut limbs = [0; N];
+    let mut i = 0;
+    while i < N {
+        limbs[i] = low[i];
+        i += 1;
+    }
+    let high = !is_zero(high);
+    let top = limbs[N - 1] >> 63 == 1;
+    let overflow = if !signed {
+        high
+    } else if negative {
+        // Only the magnitude of `MIN` has the top bit set.
+        high || (top && (limbs[N - 1] << 1 != 0 || !is_zero(limbs.split_at(N - 1).0)))
+    } else {
+        high || top
+    };
+    if negative {
+        negate(&mut limbs);
+    }
+    (limbs, overflow)
+}
+
+/// Computes `a + b`, along with a boolean indicating whether an overflow
+/// happened. If it did, the wrapped value is returned.
+pub fn limbs_add<const N: usize>(a: [u64; N], b: [u64; N], signed: bool) -> ([u64; N], bool) {
+    let mut sum = a;
+    let carry = add_in_place(&mut sum, &b, false);
+    let overflow = if signed {
+        let negative = is_negative(&a, true);
+        negative == is_negative(&b, true) && is_negative(&sum, true) != negative
+    } else {
+        carry
+    };
+    (sum, overflow)
+}
+
+/// Computes `a - b`, along with a boolean indicating whether an overflow
+/// happened. If it did, the wrapped value is returned.
+pub fn limbs_sub<const N: usize>(a: [u64; N], b: [u64; N], signed: bool) -> ([u64; N], bool) {
+    let mut difference = a;
+    let carry = add_in_place(&mut difference, &b.map(|x| !x), true);
+    let overflow = if signed {
+        let negative = is_negative(&a, true);
+        negative != is_negative(&b, true) && is_negative(&difference, true) != negative
+    } else {
+        !carry
+    };
+    (difference, overflow)
+}
+
+/// Computes `(a * b) >> shift`, where `shift < 64 * N`, rounded toward
+/// negative infinity like `mul_i128`, along with a boolean indicating whether an overflow
+/// happened. If it did, the low `64 * N` bits are returned.
+pub fn limbs_mul<const N: usize>(
+    a: [u64; N],
+    b: [u64; N],
+    signed: bool,
+    shift: u32,
+) -> ([u64; N], bool) {
+    let negative = is_negative(&a, signed) != is_negative(&b, signed);
+    let (a, b) = (magnitude(a, signed), magnitude(b, signed));
+    let mut product = [0u64; 2 * MAX_LIMBS];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, &y) in b.iter().enumerate() {
+            let sum = x as u128 * y as u128 + product[i + j] as u128 + carry as u128;
+            product[i + j] = sum as u64;
+            carry = (sum >> 64) as u64;
+        }
+        product[i + N] = carry;
+    }
+    let mut shifted = [0u64; 2 * MAX_LIMBS];
+    shr(&product, shift, &mut shifted);
+    if negative && any_low_bits(&product, shift) {
+        add_in_place(&mut shifted, &[], true);
+    }
+    from_magnitude(negative, &shifted[..2 * N], signed)
+}
+
+/// Compares two inner values.
+pub fn limbs_cmp<const N: usize>(a: &[u64; N], b: &[u64; N], signed: bool) -> Ordering {
+    match (is_negative(a, signed), is_negative(b, signed)) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        // With the same sign, two's complement values compare like unsigned
+        // ones.
+        _ => a.iter().rev().cmp(b.iter().rev()),
+    }
+}
+
+/// Returns `2^exponent`, where `-1022 <= exponent <= 1023`.
+const fn pow2(exponent: i32) -> f64 {
+    f64::from_bits(((exponent + 1023) as u64) << 52)
+}
+
+/// Converts the inner value to the nearest `f64`, after dividing it by
+/// `2^scale`, where `scale < 1023`.
+pub fn limbs_to_f64<const N: usize>(a: [u64; N], signed: bool, scale: u32) -> f64 {
+    let negative = is_negative(&a, signed);
+    let magnitude = magnitude(a, signed);
+    let Some(top) = magnitude.iter().rposition(|&limb| limb != 0) else {
+        return 0.0;
+    };
+    // Keep the top 64 significant bits, and fold the rest into the lowest bit,
+    // so that the conversion of the `u64` rounds correctly.
+    let len = 64 * top as u32 + 64 - magnitude[top].leading_zeros();
+    let shift = len.saturating_sub(64);
+    let mut window = [0];
+    shr(&magnitude, shift, &mut window);
+    let bits = window[0] | any_low_bits(&magnitude, shift) as u64;
+    // Scaling by a power of two is exact.
+    let value = bits as f64 * pow2(shift as i32) * pow2(-(scale as i32));
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Returns the inner value of `n << shift`, where `n` is an integer below
+/// 2^(64 * N - shift) in magnitude.
+pub const fn limbs_from_f64<const N: usize>(n: f64, shift: u8) -> [u64; N] {
+    let mut limbs = [0; N];
+    let bits = n.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    // Zero, since a subnormal is not an integer.
+    if biased == 0 {
+        return limbs;
+    }
+    let mut mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let mut exponent = biased - 1075;
+    if exponent < 0 {
+        // Exact, because `n` is an integer.
+        mantissa >>= -exponent;
+        exponent = 0;
+    }
+    let position = exponent as u32 + shift as u32;
+    let (limb, bit) = ((position / 64) as usize, position % 64);
+    limbs[limb] = mantissa << bit;
+    if bit > 0 && limb + 1 < N {
+        limbs[limb + 1] = mantissa >> (64 - bit);
+    }
+    if bits >> 63 == 1 {
+        negate(&mut limbs);
+    }
+    limbs
+}
+
+/// Returns the inner value with the given sign and magnitude, in units of
+/// 2^-`FRAC_BITS`, or `None` if it is out of range. The inner value is the
+/// magnitude shifted left by `pad_bits`, where `pad_bits < 64`.
+pub const fn limbs_from_parts<const N: usize>(
+    negative: bool,
+    magnitude: [u64; N],
+    signed: bool,
+    pad_bits: u8,
+) -> Option<[u64; N]> {
+    // The limb after the first `N` receives the bits shifted out.
+    let mut shifted = [0; MAX_LIMBS + 1];
+    let mut i = 0;
+    while i < N {
+        shifted[i] |= magnitude[i] << pad_bits;
+        if pad_bits > 0 {
+            shifted[i + 1] = magnitude[i] >> (64 - pad_bits);
+        }
+        i += 1;
+    }
+    let (limbs, overflow) = from_magnitude(negative, shifted.split_at(N + 1).0, signed);
+    // A negative sign only fits an unsigned format with a zero magnitude.
+    if overflow || (negative && !signed && !is_zero(&magnitude)) {
+        None
+    } else {
+        Some(limbs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_sub() {
+        let max = [u64::MAX, u64::MAX >> 1];
+        assert_eq!(limbs_add([u64::MAX, 0], [1, 0], true), ([0, 1], false));
+        assert_eq!(limbs_add(max, [1, 0], true), ([0, 1 << 63], true));
+        assert_eq!(limbs_add(max, [1, 0], false), ([0, 1 << 63], false));
+        assert_eq!(limbs_add([0, 1 << 63], [0, 1 << 63], false), ([0, 0], true));
+        assert_eq!(limbs_sub([0, 1], [1, 0], true), ([u64::MAX, 0], false));
+        assert_eq!(limbs_sub([0, 0], [1, 0], true), ([u64::MAX; 2], false));
+        assert_eq!(limbs_sub([0, 0], [1, 0], false), ([u64::MAX; 2], true));
+        assert_eq!(limbs_sub([0, 1 << 63], [1, 0], true), (max, true));
+    }
+
+    #[test]
+    fn test_mul() {
+        // 1.5 * 2.25 with 64 fractional bits
+        let (a, b) = ([1 << 63, 1], [1 << 62, 2]);
+        assert_eq!(limbs_mul(a, b, false, 64), ([3 << 61, 3], false));
+        // -1.5 * 2^-64 rounds toward negative infinity.
+        let minus_a = [1 << 63, u64::MAX - 1];
+        assert_eq!(
+            limbs_mul(minus_a, [1, 0], true, 64),
+            ([u64::MAX - 1, u64::MAX], false)
+        );
+        assert_eq!(limbs_mul(minus_a, minus_a, true, 64), ([1 << 62, 2], false));
+        // MIN * -1 overflows, and MIN * 1 does not.
+        let (min, one, minus_one) = ([0, 1 << 63], [0, 1], [0, u64::MAX]);
+        assert_eq!(limbs_mul(min, one, true, 64), (min, false));
+        assert_eq!(limbs_mul(min, minus_one, true, 64), (min, true));
+        assert!(limbs_mul([0, 1 << 63], [0, 2], false, 64).1);
+    }
+
+    #[test]
+    fn test_cmp() {
+        let (minus_one, one) = ([u64::MAX; 2], [1, 0]);
+        assert_eq!(limbs_cmp(&minus_one, &one, true), Ordering::Less);
+        assert_eq!(limbs_cmp(&minus_one, &one, false), Ordering::Greater);
+        assert_eq!(limbs_cmp(&[0, 1], &[u64::MAX, 0], true), Ordering::Greater);
+        assert_eq!(limbs_cmp(&[5, 1], &[5, 1], true), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_f64() {
+        assert_eq!(limbs_to_f64([0, 3], true, 65), 1.5);
+        assert_eq!(limbs_to_f64([u64::MAX; 2], true, 0), -1.0);
+        assert_eq!(limbs_to_f64([0, 1 << 63], true, 0), -pow2(127));
+        assert_eq!(limbs_to_f64([u64::MAX; 4], false, 0), pow2(255) * 2.0);
+        assert_eq!(limbs_from_f64::<2>(1.5 * 2.0, 63), [1 << 63, 1]);
+        assert_eq!(limbs_from_f64::<2>(-1.0, 0), [u64::MAX; 2]);
+        assert_eq!(limbs_from_f64::<3>(pow2(100), 8), [0, 1 << 44, 0]);
+        assert_eq!(limbs_from_f64::<2>(-0.0, 0), [0; 2]);
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let min = [0, 1 << 63];
+        assert_eq!(limbs_from_parts(true, min, true, 0), Some(min));
+        assert_eq!(limbs_from_parts(false, min, true, 0), None);
+        assert_eq!(limbs_from_parts(true, [1, 0], true, 0), Some([u64::MAX; 2]));
+        assert_eq!(
+            limbs_from_parts(false, [3, 1], false, 63),
+            Some([1 << 63, 1 << 63 | 1])
+        );
+        assert_eq!(limbs_from_parts(false, [0, 2], false, 63), None);
+        assert_eq!(limbs_from_parts(true, [0, 0], false, 8), Some([0, 0]));
+        assert_eq!(limbs_from_parts(true, [1, 0], false, 8), None);
+    }
+
+    #[test]
+    fn test_small() {
+        let mut a = [u64::MAX, 1];
+        assert_eq!(div_rem_small(&mut a, 10), 1);
+        assert_eq!(a, [0x3333_3333_3333_3333, 0]);
+        assert_eq!(mul_small(&mut a, 10), 0);
+        assert_eq!(a, [u64::MAX - 1, 1]);
+        truncate(&mut a, 64);
+        assert_eq!(a, [u64::MAX - 1, 0]);
+    }
+}
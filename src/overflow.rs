@@ -0,0 +1,10 @@
+/// What to do when a value does not fit in the target format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowMode {
+    /// Wrap around at the numeric bounds.
+    Wrap,
+    /// Saturate at the numeric bounds.
+    Saturate,
+    /// Panic.
+    Panic,
+}
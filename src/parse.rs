@@ -1,74 +1,436 @@
-use proc_macro2::Span;
-use syn::parse;
-use syn::parse::{Parse, ParseStream};
-use syn::{Ident, LitInt, Token, Visibility};
-
-pub struct Input {
-    #[allow(dead_code)]
-    pub visibility: Option<Visibility>,
-    pub name: Ident,
-    pub signed: bool,
-    pub int_bits: u8,
-    pub frac_bits: u8,
+use crate::limbs::is_zero;
+use crate::{Error, QNum, RoundingMode};
+
+/// Parses `s` as a `T`, rounding with `T::ROUNDING`; see `parse`.
+pub fn from_str<T: QNum>(s: &str) -> Result<T, Error> {
+    let (negative, magnitude) = parse(s.as_bytes(), T::FRAC_BITS, T::ROUNDING)?;
+    T::from_parts(negative, magnitude).ok_or(Error::Overflow)
 }
 
-/// Parses, for example:
-/// - `define_q_num!(MyNum, Q10.4)'
-/// - `define_q_num!(pub MyNum, UQ10.4)'
+/// The largest exponent magnitude that is kept; anything beyond it already
+/// overflows or rounds to zero or one ulp.
+const MAX_EXPONENT: i64 = 1_000_000;
+
+/// Parses `s` exactly, and rounds it to a multiple of 2^-`frac_bits` (where
+/// `frac_bits <= 127`) according to `rounding`. Returns the sign (`true` if
+/// negative) and the magnitude, in units of 2^-`frac_bits`.
 ///
-/// "UQ" -> signed = false
-/// "Q" -> signed = true
-impl Parse for Input {
-    fn parse(input: ParseStream) -> parse::Result<Self> {
-        let visibility = if input.peek(Token![pub]) {
-            Some(input.parse()?)
-        } else {
-            None
+/// The syntax is an optional sign followed by either
+/// - a decimal number with an optional exponent, e.g. `0.70710678` or `25e-3`,
+///   or
+/// - a binary, octal or hexadecimal number (with the prefix `0b`, `0o` or
+///   `0x`) with an optional binary exponent, e.g. `0b1.01` or `0x1.8p3`.
+///
+/// Returns `Error::InvalidSyntax` if `s` is not a number, or `Error::Overflow`
+/// if the magnitude does not fit in a `u128`.
+pub const fn parse(s: &[u8], frac_bits: u8, rounding: RoundingMode) -> Result<(bool, u128), Error> {
+    match parse_limbs(s, frac_bits, rounding) {
+        Ok((negative, [low, high])) => Ok((negative, (high as u128) << 64 | low as u128)),
+        Err(error) => Err(error),
+    }
+}
+
+/// Like `parse`, but returns the magnitude as `N` little-endian `u64` limbs,
+/// where `frac_bits < 64 * N`. Returns `Error::Overflow` if it does not fit in
+/// them.
+pub const fn parse_limbs<const N: usize>(
+    s: &[u8],
+    frac_bits: u8,
+    rounding: RoundingMode,
+) -> Result<(bool, [u64; N]), Error> {
+    let mut i = 0;
+    let negative = i < s.len() && s[i] == b'-';
+    if i < s.len() && (s[i] == b'-' || s[i] == b'+') {
+        i += 1;
+    }
+    // The number of bits per digit, or 0 for decimal.
+    let mut bits = 0;
+    if i + 1 < s.len() && s[i] == b'0' {
+        bits = match s[i + 1] {
+            b'b' | b'B' => 1,
+            b'o' | b'O' => 3,
+            b'x' | b'X' => 4,
+            _ => 0,
         };
-        let name: Ident = input.parse()?;
-        input.parse::<Token![,]>()?;
-        let next_token = input.parse::<Ident>()?.to_string();
-        let (signed, int_bits) = if next_token.starts_with("UQ") {
-            let rest = next_token.strip_prefix("UQ").unwrap();
-            (false, parse_int_bits(rest)?)
-        } else if next_token.starts_with('Q') {
-            let rest = next_token.strip_prefix('Q').unwrap();
-            (true, parse_int_bits(rest)?)
+        if bits != 0 {
+            i += 2;
+        }
+    }
+    let radix = if bits == 0 { 10 } else { 1 << bits };
+
+    // The digits, with at most one point among them.
+    let start = i;
+    let mut point = None;
+    let mut count = 0;
+    while i < s.len() {
+        if s[i] == b'.' && point.is_none() {
+            point = Some(i - start);
+        } else if digit_value(s[i]) < radix {
+            count += 1;
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    if count == 0 {
+        return Err(Error::InvalidSyntax);
+    }
+    let int_len = match point {
+        Some(point) => point,
+        None => count,
+    };
+
+    // The exponent: of ten for decimal, otherwise of two.
+    let mut exponent: i64 = 0;
+    if i < s.len() {
+        let marker = if bits == 0 { b'e' } else { b'p' };
+        if s[i] | 0x20 != marker {
+            return Err(Error::InvalidSyntax);
+        }
+        i += 1;
+        let exponent_negative = i < s.len() && s[i] == b'-';
+        if i < s.len() && (s[i] == b'-' || s[i] == b'+') {
+            i += 1;
+        }
+        if i == s.len() {
+            return Err(Error::InvalidSyntax);
+        }
+        while i < s.len() {
+            let digit = digit_value(s[i]);
+            if digit >= 10 {
+                return Err(Error::InvalidSyntax);
+            }
+            if exponent < MAX_EXPONENT {
+                exponent = exponent * 10 + digit as i64;
+            }
+            i += 1;
+        }
+        if exponent_negative {
+            exponent = -exponent;
+        }
+    }
+
+    let digits = Digits {
+        s,
+        start,
+        int_len,
+        count,
+    };
+    let parts = if bits == 0 {
+        parse_decimal(digits, exponent, frac_bits)
+    } else {
+        parse_binary(digits, bits, exponent, frac_bits)
+    };
+    let (magnitude, half, sticky) = match parts {
+        Ok(parts) => parts,
+        Err(error) => return Err(error),
+    };
+    let inexact = half || sticky;
+    let up = match rounding {
+        RoundingMode::NearestTiesEven => half && (sticky || magnitude[0] & 1 == 1),
+        RoundingMode::NearestTiesAway => half,
+        RoundingMode::Floor => negative && inexact,
+        RoundingMode::Ceil => !negative && inexact,
+        RoundingMode::TowardZero => false,
+    };
+    if !up {
+        return Ok((negative, magnitude));
+    }
+    match mul_add(magnitude, 1, 1) {
+        Some(magnitude) => Ok((negative, magnitude)),
+        None => Err(Error::Overflow),
+    }
+}
+
+/// The digits of a number, skipping the point.
+#[derive(Clone, Copy)]
+struct Digits<'a> {
+    s: &'a [u8],
+    start: usize,
+    /// The number of digits before the point.
+    int_len: usize,
+    count: usize,
+}
+
+impl Digits<'_> {
+    /// Returns the value of the digit at `index`, or 0 if it is out of bounds.
+    const fn get(self, index: i64) -> u32 {
+        if index < 0 || index >= self.count as i64 {
+            return 0;
+        }
+        let index = index as usize;
+        let skip = (index >= self.int_len) as usize;
+        digit_value(self.s[self.start + index + skip])
+    }
+}
+
+/// Returns the value of an ASCII (hexadecimal) digit, or `u32::MAX`.
+const fn digit_value(c: u8) -> u32 {
+    match c {
+        b'0'..=b'9' => (c - b'0') as u32,
+        b'a'..=b'f' => (c - b'a') as u32 + 10,
+        b'A'..=b'F' => (c - b'A') as u32 + 10,
+        _ => u32::MAX,
+    }
+}
+
+/// Returns `a * factor + addend`, or `None` if it overflows.
+const fn mul_add<const N: usize>(a: [u64; N], factor: u64, addend: u64) -> Option<[u64; N]> {
+    let mut result = [0; N];
+    let mut carry = addend;
+    let mut i = 0;
+    while i < N {
+        let product = a[i] as u128 * factor as u128 + carry as u128;
+        result[i] = product as u64;
+        carry = (product >> 64) as u64;
+        i += 1;
+    }
+    if carry == 0 {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+/// Returns `a << shift`, or `None` if a set bit is shifted out.
+const fn checked_shl<const N: usize>(a: [u64; N], shift: u32) -> Option<[u64; N]> {
+    let (limbs, bits) = ((shift / 64) as usize, shift % 64);
+    let mut result = [0; N];
+    let mut i = 0;
+    while i < N {
+        let (low, high) = if bits == 0 {
+            (a[i], 0)
         } else {
-            return Err(parse::Error::new(Span::call_site(), "Expected UQ or Q"));
+            (a[i] << bits, a[i] >> (64 - bits))
         };
-        input.parse::<Token![.]>()?;
-        let frac_bits = input.parse::<LitInt>()?.base10_parse()?;
-        Ok(Input {
-            visibility,
-            name,
-            signed,
-            int_bits,
-            frac_bits,
-        })
+        if i + limbs < N {
+            result[i + limbs] |= low;
+        } else if low != 0 {
+            return None;
+        }
+        if i + limbs + 1 < N {
+            result[i + limbs + 1] |= high;
+        } else if high != 0 {
+            return None;
+        }
+        i += 1;
     }
+    Some(result)
 }
 
-fn parse_int_bits(input: &str) -> Result<u8, parse::Error> {
-    match input.parse() {
-        Ok(x) => Ok(x),
-        Err(_) => Err(parse::Error::new(Span::call_site(), "Expected integer")),
+/// Returns the magnitude in units of 2^-`frac_bits`, truncated, plus whether
+/// the next bit (worth half a unit) is set, plus whether any later bit is set.
+type Parts<const N: usize> = Result<([u64; N], bool, bool), Error>;
+
+/// Converts decimal digits times 10^`exponent`.
+const fn parse_decimal<const N: usize>(digits: Digits, exponent: i64, frac_bits: u8) -> Parts<N> {
+    let point = digits.int_len as i64 + exponent;
+
+    let mut int = [0; N];
+    let mut k = 0;
+    while k < point {
+        // Once past the digits, only zeros are appended.
+        if k >= digits.count as i64 && is_zero(&int) {
+            break;
+        }
+        int = match mul_add(int, 10, digits.get(k) as u64) {
+            Some(int) => int,
+            None => return Err(Error::Overflow),
+        };
+        k += 1;
+    }
+    let mut magnitude = match checked_shl(int, frac_bits as u32) {
+        Some(magnitude) => magnitude,
+        None => return Err(Error::Overflow),
+    };
+
+    // Every multiple of 2^-(frac_bits + 1) has at most `frac_bits + 1`
+    // fractional digits, so the later digits only matter if they are nonzero.
+    let len = frac_bits as usize + 1;
+    let mut frac = [0u8; 256];
+    let mut j = 0;
+    while j < len {
+        frac[j] = digits.get(point + j as i64) as u8;
+        j += 1;
+    }
+    let mut sticky = false;
+    let mut k = point + len as i64;
+    if k < 0 {
+        k = 0;
+    }
+    while k < digits.count as i64 {
+        sticky |= digits.get(k) != 0;
+        k += 1;
+    }
+
+    // Double the fraction to shift out one bit at a time.
+    let mut bit = frac_bits as usize + 1;
+    let mut half = false;
+    while bit > 0 {
+        bit -= 1;
+        let mut carry = 0;
+        let mut j = len;
+        while j > 0 {
+            j -= 1;
+            let doubled = frac[j] * 2 + carry;
+            frac[j] = doubled % 10;
+            carry = doubled / 10;
+        }
+        if bit == 0 {
+            half = carry == 1;
+        } else if carry == 1 {
+            magnitude[(bit - 1) / 64] |= 1 << ((bit - 1) % 64);
+        }
     }
+    let mut j = 0;
+    while j < len {
+        sticky |= frac[j] != 0;
+        j += 1;
+    }
+    Ok((magnitude, half, sticky))
+}
+
+/// Converts digits of `bits` bits each times 2^`exponent`.
+const fn parse_binary<const N: usize>(
+    digits: Digits,
+    bits: u32,
+    exponent: i64,
+    frac_bits: u8,
+) -> Parts<N> {
+    let (mut magnitude, mut half, mut sticky) = ([0; N], false, false);
+    let mut k = 0;
+    while k < digits.count {
+        let digit = digits.get(k as i64);
+        // The position of the digit's lowest bit in the result.
+        let lowest =
+            bits as i64 * (digits.int_len as i64 - 1 - k as i64) + exponent + frac_bits as i64;
+        let mut j = 0;
+        while j < bits {
+            if digit >> j & 1 == 1 {
+                let position = lowest + j as i64;
+                if position >= 64 * N as i64 {
+                    return Err(Error::Overflow);
+                } else if position >= 0 {
+                    magnitude[position as usize / 64] |= 1 << (position % 64);
+                } else if position == -1 {
+                    half = true;
+                } else {
+                    sticky = true;
+                }
+            }
+            j += 1;
+        }
+        k += 1;
+    }
+    Ok((magnitude, half, sticky))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_int_bits;
+    use super::{parse, parse_limbs};
+    use crate::{Error, RoundingMode::*};
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse(b"5.75", 2, TowardZero), Ok((false, 0b10111)));
+        assert_eq!(parse(b"-5.75", 2, TowardZero), Ok((true, 0b10111)));
+        assert_eq!(parse(b"+.5", 1, TowardZero), Ok((false, 1)));
+        assert_eq!(parse(b"3.", 0, TowardZero), Ok((false, 3)));
+        assert_eq!(parse(b"575e-2", 2, TowardZero), Ok((false, 0b10111)));
+        assert_eq!(parse(b"0.0575E2", 2, TowardZero), Ok((false, 0b10111)));
+        assert_eq!(parse(b"0e999999999999", 2, TowardZero), Ok((false, 0)));
+        assert_eq!(parse(b"1e-999999999999", 2, Ceil), Ok((false, 1)));
+        assert_eq!(
+            parse(b"340282366920938463463374607431768211455", 0, TowardZero),
+            Ok((false, u128::MAX))
+        );
+        assert_eq!(
+            parse(b"340282366920938463463374607431768211456", 0, TowardZero),
+            Err(Error::Overflow)
+        );
+        assert_eq!(parse(b"2", 127, TowardZero), Err(Error::Overflow));
+        assert_eq!(parse(b"0.5", 127, TowardZero), Ok((false, 1 << 126)));
+        // 1/8 followed by nonzero digits, which only matter for rounding.
+        assert_eq!(parse(b"0.1250000000000001", 3, Floor), Ok((false, 1)));
+        assert_eq!(parse(b"0.1250000000000001", 3, Ceil), Ok((false, 2)));
+        assert_eq!(parse(b"0.0625", 3, NearestTiesEven), Ok((false, 0)));
+        assert_eq!(
+            parse(b"0.06250000000000001", 3, NearestTiesEven),
+            Ok((false, 1))
+        );
+    }
+
+    #[test]
+    fn test_parse_rounding() {
+        // 0.625 and 0.375 in units of 0.25
+        assert_eq!(parse(b"0.625", 2, NearestTiesEven), Ok((false, 2)));
+        assert_eq!(parse(b"0.375", 2, NearestTiesEven), Ok((false, 2)));
+        assert_eq!(parse(b"0.625", 2, NearestTiesAway), Ok((false, 3)));
+        assert_eq!(parse(b"0.6250000001", 2, NearestTiesEven), Ok((false, 3)));
+        assert_eq!(parse(b"-0.3", 2, Floor), Ok((true, 2)));
+        assert_eq!(parse(b"-0.3", 2, Ceil), Ok((true, 1)));
+        assert_eq!(parse(b"0.3", 2, Ceil), Ok((false, 2)));
+        assert_eq!(parse(b"0.49", 2, TowardZero), Ok((false, 1)));
+        assert_eq!(
+            parse(b"0.70710678", 15, NearestTiesEven),
+            Ok((false, 0x5a82))
+        );
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        assert_eq!(parse(b"0x1.8p3", 0, TowardZero), Ok((false, 12)));
+        assert_eq!(parse(b"-0X1.8P-1", 2, TowardZero), Ok((true, 3)));
+        assert_eq!(parse(b"0b101.11", 2, TowardZero), Ok((false, 0b10111)));
+        assert_eq!(parse(b"0o7.4", 1, TowardZero), Ok((false, 15)));
+        assert_eq!(parse(b"0xff", 4, TowardZero), Ok((false, 0xff0)));
+        assert_eq!(parse(b"0x1e3", 0, TowardZero), Ok((false, 0x1e3)));
+        assert_eq!(parse(b"0b0.011", 2, NearestTiesEven), Ok((false, 2)));
+        assert_eq!(parse(b"0b0.101", 2, NearestTiesEven), Ok((false, 2)));
+        assert_eq!(parse(b"0b0.1011", 2, NearestTiesEven), Ok((false, 3)));
+        assert_eq!(parse(b"0x1p127", 0, TowardZero), Ok((false, 1 << 127)));
+        assert_eq!(parse(b"0x1p128", 0, TowardZero), Err(Error::Overflow));
+    }
 
     #[test]
-    fn test_parse_int_bits_1() {
-        let x = parse_int_bits("10");
-        assert!(x.is_ok());
+    fn test_parse_limbs() {
+        assert_eq!(
+            parse_limbs(b"0x1p-200", 200, TowardZero),
+            Ok((false, [1, 0, 0, 0]))
+        );
+        assert_eq!(
+            parse_limbs(b"-1", 150, TowardZero),
+            Ok((true, [0, 0, 1 << 22]))
+        );
+        assert_eq!(
+            parse_limbs(b"0.1", 130, Ceil),
+            Ok((false, [0x6666_6666_6666_6667, 0x6666_6666_6666_6666, 0]))
+        );
+        assert_eq!(
+            parse_limbs(b"18446744073709551615", 0, TowardZero),
+            Ok((false, [u64::MAX]))
+        );
+        assert_eq!(
+            parse_limbs::<1>(b"18446744073709551616", 0, TowardZero),
+            Err(Error::Overflow)
+        );
+        assert_eq!(
+            parse_limbs::<2>(b"0x1p-1", 127, Ceil),
+            Ok((false, [0, 1 << 62]))
+        );
     }
 
     #[test]
-    fn test_parse_int_bits_2() {
-        let x = parse_int_bits("31x");
-        assert!(x.is_err());
+    fn test_parse_invalid() {
+        for s in [
+            "", "-", ".", "1..5", "1.5.", "0x", "1e", "1e+", "1p3", "1e3.5", " 1", "1 ", "inf",
+            "NaN", "0b102",
+        ] {
+            assert_eq!(
+                parse(s.as_bytes(), 2, TowardZero),
+                Err(Error::InvalidSyntax),
+                "{s}"
+            );
+        }
     }
 }
@@ -0,0 +1,243 @@
+use crate::{Error, OverflowMode, RoundingMode};
+
+/// Implemented by every type defined with `define_q_num!`.
+///
+/// This provides conversions between any two Q formats. They shift by the
+/// difference in `FRAC_BITS` directly, without a round trip through `f64`.
+///
+/// ```
+/// # use q_num::{define_q_num, OverflowMode, QNum, RoundingMode};
+/// define_q_num!(Sample, Q1.15);
+/// define_q_num!(Acc, Q8.24);
+/// let a = Sample::try_from(-0.75).unwrap();
+/// let b: Acc = a.convert();
+/// assert_eq!(f64::from(b), -0.75);
+/// let c = Acc::try_from(1.5).unwrap();
+/// let d: Sample = c.convert_with(RoundingMode::Floor, OverflowMode::Saturate);
+/// assert_eq!(d, Sample::MAX);
+/// ```
+pub trait QNum: Copy {
+    /// The format, e.g. `"Q1.15"`.
+    const Q_NOTATION: &'static str;
+    /// Whether the format is signed.
+    const SIGNED: bool;
+    /// The number of integer bits (including the sign bit, if any).
+    const INT_BITS: u8;
+    /// The number of fractional bits.
+    const FRAC_BITS: u8;
+    /// The smallest value.
+    const MIN: Self;
+    /// The largest value.
+    const MAX: Self;
+    /// The default rounding mode.
+    const ROUNDING: RoundingMode;
+
+    /// Returns the value as a sign (`true` if negative) and a magnitude, in
+    /// units of 2^-`FRAC_BITS`.
+    #[doc(hidden)]
+    fn to_parts(self) -> (bool, u128);
+
+    /// Builds a value from a sign (`true` if negative) and a magnitude, in units
+    /// of 2^-`FRAC_BITS`. Returns `None` if it is out of range.
+    #[doc(hidden)]
+    fn from_parts(negative: bool, magnitude: u128) -> Option<Self>;
+
+    /// Like `from_parts`, but wraps around at the numeric bounds.
+    #[doc(hidden)]
+    fn from_parts_wrapping(negative: bool, magnitude: u128) -> Self;
+
+    /// Converts to the format `T`, which must be able to represent every value
+    /// of this format exactly. Otherwise, this fails to compile.
+    fn convert<T: QNum>(self) -> T {
+        const {
+            assert!(
+                is_lossless(
+                    Self::SIGNED,
+                    Self::INT_BITS,
+                    Self::FRAC_BITS,
+                    T::SIGNED,
+                    T::INT_BITS,
+                    T::FRAC_BITS
+                ),
+                "the conversion is not lossless; use `try_convert` or `convert_with`"
+            );
+        }
+        self.convert_with(RoundingMode::TowardZero, OverflowMode::Panic)
+    }
+
+    /// Converts to the format `T`, rounding with `T::ROUNDING`. Returns
+    /// `Error::Overflow` if the value is out of range for `T`.
+    fn try_convert<T: QNum>(self) -> Result<T, Error> {
+        let (negative, magnitude, overflow) = rescale::<Self, T>(self, T::ROUNDING);
+        if overflow {
+            return Err(Error::Overflow);
+        }
+        T::from_parts(negative, magnitude).ok_or(Error::Overflow)
+    }
+
+    /// Converts to the format `T`, rounding according to `rounding` and
+    /// handling out-of-range values according to `overflow`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is out of range for `T` and `overflow` is
+    /// `OverflowMode::Panic`.
+    fn convert_with<T: QNum>(self, rounding: RoundingMode, overflow: OverflowMode) -> T {
+        let (negative, magnitude, lost) = rescale::<Self, T>(self, rounding);
+        let value = if lost {
+            None
+        } else {
+            T::from_parts(negative, magnitude)
+        };
+        match (value, overflow) {
+            (Some(value), _) => value,
+            (None, OverflowMode::Wrap) => T::from_parts_wrapping(negative, magnitude),
+            (None, OverflowMode::Saturate) if negative => T::MIN,
+            (None, OverflowMode::Saturate) => T::MAX,
+            (None, OverflowMode::Panic) => panic!("value is out of range for {}", T::Q_NOTATION),
+        }
+    }
+
+    /// Multiplies `self` by `rhs` exactly, returning the product in the format
+    /// `O`, like a hardware multiply-accumulate unit.
+    ///
+    /// `O` must be able to represent every product exactly. Otherwise, this
+    /// fails to compile. For example, the product of a `Q6.2` and a `Q4.4` fits
+    /// in a `Q10.6`, which can be defined with `define_q_num!(P, Q6.2 * Q4.4)`.
+    fn widening_mul<R: QNum, O: QNum>(self, rhs: R) -> O {
+        const {
+            assert!(
+                holds_product(
+                    (Self::SIGNED, Self::INT_BITS, Self::FRAC_BITS),
+                    (R::SIGNED, R::INT_BITS, R::FRAC_BITS),
+                    (O::SIGNED, O::INT_BITS, O::FRAC_BITS),
+                ),
+                "the output format cannot hold every product exactly"
+            );
+        }
+        let (lhs_negative, lhs_magnitude) = self.to_parts();
+        let (rhs_negative, rhs_magnitude) = rhs.to_parts();
+        // Neither the product nor the shift can overflow, because `O` holds
+        // every product.
+        let magnitude =
+            (lhs_magnitude * rhs_magnitude) << (O::FRAC_BITS - Self::FRAC_BITS - R::FRAC_BITS);
+        O::from_parts_wrapping(lhs_negative != rhs_negative, magnitude)
+    }
+}
+
+/// Returns the parts of `value`, rescaled from units of 2^-`S::FRAC_BITS` to
+/// units of 2^-`T::FRAC_BITS`, along with a boolean indicating whether the
+/// magnitude overflowed a `u128`, in which case the value is out of range for
+/// `T`.
+fn rescale<S: QNum, T: QNum>(value: S, rounding: RoundingMode) -> (bool, u128, bool) {
+    let (negative, magnitude) = value.to_parts();
+    let (from, to) = (S::FRAC_BITS as u32, T::FRAC_BITS as u32);
+    if to >= from {
+        // If the shift overflows, the low bits are kept, as needed for
+        // `from_parts_wrapping`.
+        let overflow = magnitude.leading_zeros() < to - from;
+        (negative, magnitude.wrapping_shl(to - from), overflow)
+    } else {
+        let magnitude = shr_round(negative, magnitude, from - to, rounding);
+        (negative, magnitude, false)
+    }
+}
+
+/// Returns `magnitude >> shift`, rounded according to `rounding`, where
+/// `negative` is the sign of the value and `1 <= shift <= 127`.
+pub(crate) fn shr_round(
+    negative: bool,
+    magnitude: u128,
+    shift: u32,
+    rounding: RoundingMode,
+) -> u128 {
+    let quotient = magnitude >> shift;
+    let remainder = magnitude & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let up = match rounding {
+        RoundingMode::NearestTiesEven => {
+            remainder > half || (remainder == half && quotient % 2 == 1)
+        }
+        RoundingMode::NearestTiesAway => remainder >= half,
+        RoundingMode::Floor => negative && remainder != 0,
+        RoundingMode::Ceil => !negative && remainder != 0,
+        RoundingMode::TowardZero => false,
+    };
+    quotient + up as u128
+}
+
+/// Returns whether every value of the first format is exactly representable in
+/// the second format.
+const fn is_lossless(
+    from_signed: bool,
+    from_int_bits: u8,
+    from_frac_bits: u8,
+    to_signed: bool,
+    to_int_bits: u8,
+    to_frac_bits: u8,
+) -> bool {
+    let int_ok = match (from_signed, to_signed) {
+        (true, true) | (false, false) => to_int_bits >= from_int_bits,
+        (false, true) => to_int_bits > from_int_bits,
+        (true, false) => false,
+    };
+    int_ok && to_frac_bits >= from_frac_bits
+}
+
+/// Returns whether the format `out` can represent every product of a value in
+/// the format `lhs` and a value in the format `rhs`. Each format is given as
+/// `(signed, int_bits, frac_bits)`.
+const fn holds_product(lhs: (bool, u8, u8), rhs: (bool, u8, u8), out: (bool, u8, u8)) -> bool {
+    let int_bits = lhs.1 as u16 + rhs.1 as u16;
+    let frac_bits = lhs.2 as u16 + rhs.2 as u16;
+    let int_ok = if lhs.0 || rhs.0 {
+        out.0 && out.1 as u16 >= int_bits
+    } else if out.0 {
+        out.1 as u16 > int_bits
+    } else {
+        out.1 as u16 >= int_bits
+    };
+    int_ok && out.2 as u16 >= frac_bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shr_round() {
+        use RoundingMode::*;
+        // 0b1010 / 4 == 2.5 and 0b1011 / 4 == 2.75
+        assert_eq!(shr_round(false, 0b1010, 2, NearestTiesEven), 2);
+        assert_eq!(shr_round(false, 0b1110, 2, NearestTiesEven), 4);
+        assert_eq!(shr_round(false, 0b1010, 2, NearestTiesAway), 3);
+        assert_eq!(shr_round(true, 0b1010, 2, Floor), 3);
+        assert_eq!(shr_round(false, 0b1010, 2, Floor), 2);
+        assert_eq!(shr_round(false, 0b1011, 2, Ceil), 3);
+        assert_eq!(shr_round(true, 0b1011, 2, Ceil), 2);
+        assert_eq!(shr_round(true, 0b1011, 2, TowardZero), 2);
+        assert_eq!(shr_round(false, u128::MAX, 127, NearestTiesEven), 2);
+    }
+
+    #[test]
+    fn test_holds_product() {
+        assert!(holds_product((true, 6, 2), (true, 4, 4), (true, 10, 6)));
+        assert!(!holds_product((true, 6, 2), (true, 4, 4), (true, 9, 6)));
+        assert!(!holds_product((true, 6, 2), (true, 4, 4), (true, 10, 5)));
+        assert!(holds_product((true, 6, 2), (false, 4, 4), (true, 10, 6)));
+        assert!(!holds_product((true, 6, 2), (false, 4, 4), (false, 12, 6)));
+        assert!(holds_product((false, 6, 2), (false, 4, 4), (false, 10, 6)));
+        assert!(holds_product((false, 6, 2), (false, 4, 4), (true, 11, 6)));
+        assert!(!holds_product((false, 6, 2), (false, 4, 4), (true, 10, 6)));
+    }
+
+    #[test]
+    fn test_is_lossless() {
+        assert!(is_lossless(true, 1, 15, true, 8, 24));
+        assert!(!is_lossless(true, 8, 24, true, 1, 15));
+        assert!(is_lossless(false, 4, 2, true, 5, 2));
+        assert!(!is_lossless(false, 4, 2, true, 4, 2));
+        assert!(!is_lossless(true, 4, 2, false, 8, 2));
+        assert!(!is_lossless(true, 4, 2, true, 4, 1));
+    }
+}
@@ -0,0 +1,92 @@
+/// How to round a value that falls between two representable values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Round to the nearest representable value; on a tie, round to the one
+    /// whose least significant bit is zero.
+    NearestTiesEven,
+    /// Round to the nearest representable value; on a tie, round away from
+    /// zero.
+    NearestTiesAway,
+    /// Round toward negative infinity.
+    Floor,
+    /// Round toward positive infinity.
+    Ceil,
+    /// Round toward zero, i.e. truncate.
+    TowardZero,
+}
+
+impl RoundingMode {
+    /// Rounds `value` to an integer according to this mode.
+    ///
+    /// NaN and the infinities are returned unchanged.
+    pub const fn round(self, value: f64) -> f64 {
+        // At or above 2^52 (and for NaN and the infinities), there is no
+        // fractional part.
+        const LIMIT: f64 = 4_503_599_627_370_496.0;
+        if !(-LIMIT < value && value < LIMIT) {
+            return value;
+        }
+        let int = value as i64;
+        let trunc = int as f64;
+        // Exact, because `value` and `trunc` share their exponent or `trunc`
+        // is zero.
+        let diff = value - trunc;
+        let odd = int % 2 != 0;
+        let up = match self {
+            RoundingMode::NearestTiesEven => diff > 0.5 || (diff == 0.5 && odd),
+            RoundingMode::NearestTiesAway => diff >= 0.5,
+            RoundingMode::Floor | RoundingMode::TowardZero => false,
+            RoundingMode::Ceil => diff > 0.0,
+        };
+        let down = match self {
+            RoundingMode::NearestTiesEven => diff < -0.5 || (diff == -0.5 && odd),
+            RoundingMode::NearestTiesAway => diff <= -0.5,
+            RoundingMode::Floor => diff < 0.0,
+            RoundingMode::Ceil | RoundingMode::TowardZero => false,
+        };
+        if up {
+            trunc + 1.0
+        } else if down {
+            trunc - 1.0
+        } else {
+            trunc
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundingMode::*;
+
+    #[test]
+    fn test_round_ties() {
+        let values = [-2.5, -1.5, -0.5, 0.5, 1.5, 2.5];
+        let round = |mode: super::RoundingMode| values.map(|v| mode.round(v));
+        assert_eq!(round(NearestTiesEven), [-2.0, -2.0, -0.0, 0.0, 2.0, 2.0]);
+        assert_eq!(round(NearestTiesAway), [-3.0, -2.0, -1.0, 1.0, 2.0, 3.0]);
+        assert_eq!(round(Floor), [-3.0, -2.0, -1.0, 0.0, 1.0, 2.0]);
+        assert_eq!(round(Ceil), [-2.0, -1.0, 0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(round(TowardZero), [-2.0, -1.0, 0.0, 0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_round_non_ties() {
+        assert_eq!(NearestTiesEven.round(-0.3), 0.0);
+        assert_eq!(NearestTiesAway.round(2.7), 3.0);
+        assert_eq!(Floor.round(-0.3), -1.0);
+        assert_eq!(Ceil.round(0.3), 1.0);
+        assert_eq!(TowardZero.round(-2.7), -2.0);
+    }
+
+    #[test]
+    fn test_round_large_and_special() {
+        let big = 1e300;
+        assert_eq!(Floor.round(big), big);
+        assert_eq!(
+            Ceil.round(-4_503_599_627_370_497.0),
+            -4_503_599_627_370_497.0
+        );
+        assert!(NearestTiesEven.round(f64::NAN).is_nan());
+        assert_eq!(Floor.round(f64::INFINITY), f64::INFINITY);
+    }
+}
@@ -0,0 +1,173 @@
+//! Multiplication and division of 128-bit inner values, which have no wider
+//! primitive type for the intermediate result. A 256-bit intermediate is kept
+//! as a `(high, low)` pair of `u128`s.
+
+/// Returns the full 256-bit product of `a` and `b`.
+const fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    let (a_high, a_low) = (a >> 64, a as u64 as u128);
+    let (b_high, b_low) = (b >> 64, b as u64 as u128);
+    let low = a_low * b_low;
+    let cross1 = a_low * b_high;
+    let cross2 = a_high * b_low;
+    // Below 3 * 2^64, so it cannot overflow.
+    let middle = (low >> 64) + (cross1 as u64 as u128) + (cross2 as u64 as u128);
+    let high = a_high * b_high + (cross1 >> 64) + (cross2 >> 64) + (middle >> 64);
+    (high, (middle << 64) | (low as u64 as u128))
+}
+
+/// Shifts a 256-bit value right by `shift < 128` bits.
+const fn shr(high: u128, low: u128, shift: u32) -> (u128, u128) {
+    if shift == 0 {
+        (high, low)
+    } else {
+        (high >> shift, (low >> shift) | (high << (128 - shift)))
+    }
+}
+
+/// Shifts a 256-bit value left by `shift < 128` bits, dropping the bits shifted
+/// out.
+const fn shl(high: u128, low: u128, shift: u32) -> (u128, u128) {
+    if shift == 0 {
+        (high, low)
+    } else {
+        ((high << shift) | (low >> (128 - shift)), low << shift)
+    }
+}
+
+/// Returns `(a << frac_bits) / b` as a 256-bit value, rounded toward zero.
+///
+/// # Panics
+///
+/// Panics if `b == 0`.
+const fn div_magnitude(a: u128, b: u128, frac_bits: u8) -> (u128, u128) {
+    let (mut high, mut low) = (0, a / b);
+    let mut remainder = a % b;
+    // Long division, one fractional bit at a time. The remainder is below `b`,
+    // so doubling it needs at most one bit more.
+    let mut i = 0;
+    while i < frac_bits {
+        let carry = remainder >> 127;
+        remainder <<= 1;
+        let bit = carry == 1 || remainder >= b;
+        if bit {
+            remainder = remainder.wrapping_sub(b);
+        }
+        (high, low) = shl(high, low, 1);
+        low |= bit as u128;
+        i += 1;
+    }
+    (high, low)
+}
+
+/// Computes `(a * b) >> shift`, where `shift < 128`, along with a boolean
+/// indicating whether the result overflowed a `u128`. If it did, the low 128
+/// bits are returned.
+pub const fn mul_u128(a: u128, b: u128, shift: u8) -> (u128, bool) {
+    let (high, low) = widening_mul(a, b);
+    let (high, low) = shr(high, low, shift as u32);
+    (low, high != 0)
+}
+
+/// Computes `(a * b) >> shift`, where `shift < 128`, rounded toward negative
+/// infinity, along with a boolean indicating whether the result overflowed an
+/// `i128`. If it did, the low 128 bits are returned.
+pub const fn mul_i128(a: i128, b: i128, shift: u8) -> (i128, bool) {
+    let negative = (a < 0) != (b < 0);
+    let (high, low) = widening_mul(a.unsigned_abs(), b.unsigned_abs());
+    let inexact = shift > 0 && low & (u128::MAX >> (128 - shift as u32)) != 0;
+    let (mut high, mut low) = shr(high, low, shift as u32);
+    // Rounding the magnitude up rounds a negative result down.
+    if negative && inexact {
+        let carry;
+        (low, carry) = low.overflowing_add(1);
+        high += carry as u128;
+    }
+    to_i128(negative, high, low)
+}
+
+/// Returns the signed value with the given sign and 256-bit magnitude, along
+/// with a boolean indicating whether it overflowed an `i128`. If it did, the
+/// low 128 bits are returned.
+const fn to_i128(negative: bool, high: u128, low: u128) -> (i128, bool) {
+    if negative {
+        (
+            low.wrapping_neg() as i128,
+            high != 0 || low > i128::MIN.unsigned_abs(),
+        )
+    } else {
+        (low as i128, high != 0 || low > i128::MAX as u128)
+    }
+}
+
+/// Computes `((a << frac_bits) / b) << pad_bits`, rounded toward zero, along
+/// with a boolean indicating whether the result overflowed a `u128`. If it did,
+/// the low 128 bits are returned.
+///
+/// # Panics
+///
+/// Panics if `b == 0`.
+pub const fn div_u128(a: u128, b: u128, frac_bits: u8, pad_bits: u8) -> (u128, bool) {
+    let (high, low) = div_magnitude(a, b, frac_bits);
+    let (shifted, low) = shl(high, low, pad_bits as u32);
+    let lost = pad_bits > 0 && high >> (128 - pad_bits as u32) != 0;
+    (low, shifted != 0 || lost)
+}
+
+/// Computes `((a << frac_bits) / b) << pad_bits`, rounded toward zero, along
+/// with a boolean indicating whether the result overflowed an `i128`. If it
+/// did, the low 128 bits are returned.
+///
+/// # Panics
+///
+/// Panics if `b == 0`.
+pub const fn div_i128(a: i128, b: i128, frac_bits: u8, pad_bits: u8) -> (i128, bool) {
+    let negative = (a < 0) != (b < 0);
+    let (high, low) = div_magnitude(a.unsigned_abs(), b.unsigned_abs(), frac_bits);
+    let (shifted, low) = shl(high, low, pad_bits as u32);
+    let lost = pad_bits > 0 && high >> (128 - pad_bits as u32) != 0;
+    let (bits, overflow) = to_i128(negative, shifted, low);
+    (bits, overflow || lost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widening_mul() {
+        assert_eq!(widening_mul(3, 5), (0, 15));
+        assert_eq!(widening_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(widening_mul(1 << 127, 4), (2, 0));
+    }
+
+    #[test]
+    fn test_mul() {
+        // 1.5 * 2.25 in UQ64.64
+        assert_eq!(mul_u128(3 << 63, 9 << 62, 64), (27 << 61, false));
+        assert_eq!(mul_u128(1 << 127, 1 << 64, 64), (1 << 127, false));
+        assert_eq!(mul_u128(1 << 127, 2 << 64, 64), (0, true));
+        // -1.5 rounds toward negative infinity.
+        assert_eq!(mul_i128(-3 << 63, 1, 64), (-2, false));
+        assert_eq!(mul_i128(3 << 63, 1, 64), (1, false));
+        assert_eq!(mul_i128(-3 << 63, -(3 << 63), 64), (9 << 62, false));
+        assert_eq!(mul_i128(i128::MIN, 1 << 64, 64), (i128::MIN, false));
+        assert_eq!(mul_i128(i128::MIN, -1 << 64, 64), (i128::MIN, true));
+        assert_eq!(mul_i128(-1, -1, 0), (1, false));
+    }
+
+    #[test]
+    fn test_div() {
+        // 1.5 / 0.25 in UQ64.64
+        assert_eq!(div_u128(3 << 63, 1 << 62, 64, 0), (6 << 64, false));
+        assert_eq!(div_u128(1, 3, 4, 0), (5, false));
+        assert!(div_u128(1 << 127, 1, 64, 0).1);
+        // Q60.64 (padded by 4 bits): 1 / 3
+        let third = (u64::MAX / 3) as i128;
+        assert_eq!(div_i128(1 << 68, 3 << 68, 64, 4), (third << 4, false));
+        assert!(div_u128(1 << 127, 1 << 64, 64, 4).1);
+        // Rounded toward zero.
+        assert_eq!(div_i128(-1, 3, 4, 0), (-5, false));
+        assert_eq!(div_i128(i128::MIN, 1 << 64, 64, 0), (i128::MIN, false));
+        assert_eq!(div_i128(i128::MIN, -1 << 64, 64, 0), (i128::MIN, true));
+    }
+}
@@ -1,4 +1,4 @@
-use q_num::define_q_num;
+use q_num::{define_q_num, Error, OverflowMode, QNum, RoundingMode};
 
 /// Spot check the macro-generated constants for `Q12.5`.
 #[test]
@@ -17,6 +17,265 @@ fn test_q12p5_constants() {
     assert_eq!(X::MAX_FLOAT, 2047.96875);
 }
 
+/// `MIN` and `MAX` are left-aligned, like every other value.
+#[test]
+fn test_min_max() {
+    define_q_num!(X, Q4.2);
+    assert_eq!(f64::from(X::MIN), X::MIN_FLOAT);
+    assert_eq!(f64::from(X::MAX), X::MAX_FLOAT);
+    assert_eq!(X::MIN.to_bits() as u8, 0x80);
+    assert_eq!(X::MAX.to_bits() as u8, 0x7C);
+    define_q_num!(Y, UQ4.2);
+    assert_eq!(f64::from(Y::MIN), Y::MIN_FLOAT);
+    assert_eq!(f64::from(Y::MAX), Y::MAX_FLOAT);
+}
+
+/// Spot check the macro-generated constants for `Q2.30`, which needs a
+/// conversion factor of 2^31.
+#[test]
+fn test_q2p30_constants() {
+    define_q_num!(X, Q2.30);
+    assert_eq!(X::TOTAL_BITS, 32);
+    assert_eq!(X::PAD_BITS, 0);
+    assert_eq!(X::CONVERSION_FACTOR, 1_073_741_824.0);
+    assert_eq!(X::MIN_FLOAT, -2.0);
+    assert_eq!(f64::from(X::MIN), -2.0);
+    assert_eq!(X::MAX.to_bits(), i32::MAX);
+}
+
+/// Spot check the macro-generated constants for `Q40.20`.
+#[test]
+fn test_q40p20_constants() {
+    define_q_num!(X, Q40.20);
+    assert_eq!(X::TOTAL_BITS, 64);
+    assert_eq!(std::mem::size_of::<X>(), 8);
+    assert_eq!(X::PAD_BITS, 4);
+    assert_eq!(X::CONVERSION_FACTOR, 16_777_216.0);
+    assert_eq!(X::USED_MASK as u64, 0xFFFF_FFFF_FFFF_FFF0);
+    assert_eq!(X::MIN_FLOAT, -549_755_813_888.0);
+}
+
+/// Multiplication and division of 64-bit formats go through a 128-bit
+/// intermediate.
+#[test]
+fn test_64_bit_mul_div() {
+    define_q_num!(X, Q40.20);
+    let x1 = X::try_from(-123_456.75).unwrap();
+    let x2 = X::try_from(4_000.5).unwrap();
+    let x3 = X::try_from(-493_888_728.375).unwrap();
+    assert_eq!(x1 * x2, x3);
+    assert_eq!(x3 / x2, x1);
+    define_q_num!(Y, UQ32.32);
+    let y1 = Y::try_from(65_535.5).unwrap();
+    let y2 = Y::try_from(65_536.0).unwrap();
+    let y3 = Y::try_from(4_294_934_528.0).unwrap();
+    assert_eq!(y1 * y2, y3);
+    assert_eq!(y3 / y1, y2);
+}
+
+/// Spot check the macro-generated constants of 128-bit formats.
+#[test]
+fn test_128_bit_constants() {
+    define_q_num!(X, Q32.64);
+    assert_eq!(X::TOTAL_BITS, 128);
+    assert_eq!(std::mem::size_of::<X>(), 16);
+    assert_eq!(X::PAD_BITS, 32);
+    assert_eq!(X::USED_MASK as u128, !0 << 32);
+    assert_eq!(X::MIN_FLOAT, -2_147_483_648.0);
+    assert_eq!(X::MAX.to_bits(), i128::MAX & !0 << 32);
+    define_q_num!(Y, UQ128.0);
+    assert_eq!((Y::PAD_BITS, Y::MAX.to_bits()), (0, u128::MAX));
+    assert_eq!(Y::MAX.to_string(), u128::MAX.to_string());
+    assert_eq!(Y::MAX.to_f32(), f32::INFINITY);
+    define_q_num!(Z, Q1.127);
+    assert_eq!((Z::MIN.to_bits(), f64::from(Z::MIN)), (i128::MIN, -1.0));
+}
+
+/// Multiplication and division of 128-bit formats go through a 256-bit
+/// intermediate.
+#[test]
+fn test_128_bit_mul_div() {
+    define_q_num!(X, Q32.64);
+    let x1 = X::try_from(-123_456.75).unwrap();
+    let x2 = X::try_from(4_000.5).unwrap();
+    let x3 = X::try_from(-493_888_728.375).unwrap();
+    assert_eq!(x1 * x2, x3);
+    assert_eq!(x3 / x2, x1);
+    let third = X::from(1i8) / X::from(3i8);
+    assert_eq!(third.to_bits(), (u64::MAX / 3) as i128 * (1 << 32));
+    assert!(X::MAX.overflowing_mul(X::from(2i8)).1);
+    assert_eq!(X::MIN.checked_div(X::from(-1i8)), None);
+    assert_eq!(X::MIN.saturating_mul(X::from(2i8)), X::MIN);
+    define_q_num!(Y, UQ64.64);
+    let y1 = Y::try_from(65_535.5).unwrap();
+    let y2 = Y::try_from(4_294_967_296.0).unwrap();
+    let y3 = Y::try_from(281_472_829_227_008.0).unwrap();
+    assert_eq!(y1 * y2, y3);
+    assert_eq!(y3 / y1, y2);
+    assert_eq!(Y::MAX.checked_mul(y2), None);
+    define_q_num!(Z, Q1.127);
+    let half = Z::try_from(-0.5).unwrap();
+    assert_eq!(half * half, Z::try_from(0.25).unwrap());
+    let quotient = half / Z::try_from(0.75).unwrap();
+    assert_eq!(quotient.to_bits(), -((u128::MAX / 3) as i128));
+}
+
+/// Conversions, formatting and parsing of 128-bit formats.
+#[test]
+fn test_128_bit_conversions() {
+    define_q_num!(X, Q32.64);
+    let x = X::try_from(-2.5).unwrap();
+    assert_eq!((x.to_int_trunc(), x.to_int_round()), (-2, -3));
+    assert_eq!(X::from(i32::MIN).to_int_floor(), i32::MIN as i128);
+    assert_eq!(X::try_from(i64::MAX), Err(Error::Overflow));
+    assert_eq!(
+        X::from_f64_wrapping(2_147_483_648.5),
+        X::try_from(-2_147_483_647.5).unwrap()
+    );
+    assert_eq!(x.to_string(), "-2.5");
+    assert_eq!("-2.5".parse::<X>(), Ok(x));
+    assert_eq!(
+        X::MAX.to_string(),
+        "2147483647.9999999999999999999457898913757247782996273599565029144287109375"
+    );
+    assert_eq!(X::MAX.to_string().parse::<X>(), Ok(X::MAX));
+    define_q_num!(Y, UQ128.0);
+    assert_eq!(Y::from(u64::MAX).to_int_round(), u64::MAX as u128);
+    assert_eq!(Y::from_f64_wrapping(-1.0), Y::MAX);
+    assert_eq!(Y::MAX.to_string().parse::<Y>(), Ok(Y::MAX));
+    assert_eq!(Y::from(u128::MAX), Y::MAX);
+    assert_eq!(Y::try_from(-1i128), Err(Error::Overflow));
+    assert_eq!(
+        Y::try_from(i128::MAX).unwrap().to_int_floor(),
+        i128::MAX as u128
+    );
+    define_q_num!(I, Q128.0);
+    assert_eq!(I::from(i128::MIN), I::MIN);
+    assert_eq!(I::try_from(u128::MAX), Err(Error::Overflow));
+    assert_eq!(I::try_from(i128::MAX as u128), Ok(I::MAX));
+    // Integers outside the `i64` range.
+    define_q_num!(H, Q100.28);
+    assert_eq!(H::try_from(1i128 << 90).unwrap().to_int_floor(), 1 << 90);
+    assert_eq!(H::try_from(-1i128 << 99), Ok(H::MIN));
+    assert_eq!(H::try_from(1i128 << 99), Err(Error::Overflow));
+    assert_eq!(H::try_from(1u128 << 98).unwrap().to_int_floor(), 1 << 98);
+    assert_eq!(H::try_from(u128::MAX), Err(Error::Overflow));
+    define_q_num!(Z, UQ64.64);
+    assert_eq!(Z::MAX.to_f32(), 18_446_744_073_709_551_616.0);
+    // Rescaling to 126 fractional bits overflows the `u128` magnitude.
+    define_q_num!(A, Q64.0);
+    define_q_num!(B, Q2.126);
+    define_q_num!(C, Q8.8);
+    assert_eq!(A::from(1i8).try_convert::<B>(), B::try_from(1));
+    assert_eq!(A::from(4i8).try_convert::<B>(), Err(Error::Overflow));
+    assert_eq!(C::from(16i8).try_convert::<B>(), Err(Error::Overflow));
+    let convert = |a: A, overflow| a.convert_with::<B>(RoundingMode::Floor, overflow);
+    assert_eq!(convert(A::from(4i8), OverflowMode::Saturate), B::MAX);
+    assert_eq!(convert(A::from(-4i8), OverflowMode::Saturate), B::MIN);
+    assert_eq!(convert(A::from(4i8), OverflowMode::Wrap), B::from_bits(0));
+    let panicked = std::panic::catch_unwind(|| convert(A::from(4i8), OverflowMode::Panic));
+    assert!(panicked.is_err());
+}
+
+/// Formats wider than 128 bits are stored in `u64` limbs.
+#[test]
+fn test_limbs_constants() {
+    define_q_num!(X, Q64.192);
+    assert_eq!((X::TOTAL_BITS, X::USED_BITS, X::PAD_BITS), (256, 256, 0));
+    assert_eq!(std::mem::size_of::<X>(), 32);
+    assert_eq!(X::MIN.to_bits(), [0, 0, 0, 1 << 63]);
+    assert_eq!(f64::from(X::MIN), -9_223_372_036_854_775_808.0);
+    assert_eq!(
+        X::MAX.to_bits(),
+        [u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1]
+    );
+    define_q_num!(Y, UQ100.100);
+    assert_eq!((Y::TOTAL_BITS, Y::PAD_BITS), (256, 56));
+    assert_eq!(Y::USED_MASK[0], u64::MAX << 56);
+    assert_eq!(Y::from_bits([u64::MAX; 4]), Y::MAX);
+    assert_eq!(Y::MIN.to_bits(), [0; 4]);
+}
+
+/// Arithmetic, comparison and formatting of formats stored in limbs.
+#[test]
+fn test_limbs_arithmetic() {
+    define_q_num!(X, Q64.192);
+    let f = |v: f64| X::try_from(v).unwrap();
+    let (a, b) = (f(-123_456.75), f(4_000.5));
+    assert_eq!(a + b, f(-119_456.25));
+    assert_eq!(a - b, f(-127_457.25));
+    assert_eq!(a * b, f(-493_888_728.375));
+    assert_eq!(-a, f(123_456.75));
+    assert!(a < b && b < f(4_000.75) && X::MIN < a && b < X::MAX);
+    assert_eq!(X::MAX.checked_add(b), None);
+    assert_eq!(X::MIN.saturating_sub(b), X::MIN);
+    assert_eq!(X::MAX.wrapping_add(f(2f64.powi(-192))), X::MIN);
+    assert_eq!(X::MIN.checked_neg(), None);
+    assert_eq!(X::MAX.saturating_mul(a), X::MIN);
+    // -2^-192 * 0.5 rounds toward negative infinity.
+    let tiny = X::from_bits([1, 0, 0, 0]);
+    assert_eq!(-tiny * f(0.5), -tiny);
+    let mut c = a;
+    c *= f(2.0);
+    c -= a;
+    assert_eq!(c, a);
+    define_q_num!(Y, UQ100.100, overflow = saturate);
+    let y = Y::try_from(0.75).unwrap();
+    assert_eq!(y * y, Y::try_from(0.5625).unwrap());
+    assert_eq!(y - Y::MAX, Y::MIN);
+    assert_eq!(Y::MAX * Y::try_from(2.0).unwrap(), Y::MAX);
+}
+
+/// Formatting and `f64` conversions of formats stored in limbs.
+#[test]
+fn test_limbs_formatting() {
+    define_q_num!(X, Q64.192);
+    let x = X::try_from(-2.5).unwrap();
+    assert_eq!(x.to_string(), "-2.5");
+    assert_eq!(
+        format!("{x:+8.2}|{x:e}|{x:?}"),
+        "   -2.50|-2.5e0|X(-2.5, Q64.192)"
+    );
+    assert_eq!(f64::from(x), -2.5);
+    assert_eq!(X::MIN.to_string(), "-9223372036854775808");
+    let tiny = X::from_bits([1, 0, 0, 0]).to_string();
+    assert_eq!(tiny.len(), 2 + 192);
+    assert!(tiny.starts_with(&format!("0.{}1593091911", "0".repeat(57))));
+    assert!(tiny.ends_with("12890625"));
+    assert_eq!(X::try_from(f64::NAN), Err(Error::NotANumber));
+    assert!(X::try_from(2f64.powi(63)).is_err());
+    define_q_num!(Y, UQ200.0);
+    let y = Y::try_from(2f64.powi(199)).unwrap();
+    assert_eq!(
+        y.to_string(),
+        "803469022129495137770981046170581301261101496891396417650688"
+    );
+    assert_eq!(f64::from(Y::MAX), 2f64.powi(200));
+}
+
+/// Parsing of formats stored in limbs, at run time and with `q!`.
+#[test]
+fn test_limbs_parsing() {
+    use q_num::q;
+    define_q_num!(X, Q64.192);
+    const HALF: X = q!(X, -0.5);
+    assert_eq!(HALF, X::try_from(-0.5).unwrap());
+    assert_eq!("0x1p-192".parse(), Ok(X::from_bits([1, 0, 0, 0])));
+    assert_eq!(X::MAX.to_string().parse(), Ok(X::MAX));
+    assert_eq!("-9223372036854775808".parse(), Ok(X::MIN));
+    assert_eq!("9223372036854775808".parse::<X>(), Err(Error::Overflow));
+    assert_eq!("1e-999".parse::<X>(), Ok(q!(X, 0)));
+    assert_eq!("0.5.".parse::<X>(), Err(Error::InvalidSyntax));
+    define_q_num!(Y, UQ100.100, rounding = nearest_even);
+    let bits = [0x9a << 56, 0x9999_9999_9999_9999, 0x199_9999, 0];
+    assert_eq!(q!(Y, 0.1).to_bits(), bits);
+    assert_eq!("0x1p-100".parse(), Ok(Y::from_bits([1 << 56, 0, 0, 0])));
+    assert_eq!(Y::MAX.to_string().parse(), Ok(Y::MAX));
+    assert_eq!("-0".parse(), Ok(Y::MIN));
+    assert_eq!("-0x1p-100".parse::<Y>(), Err(Error::Overflow));
+    assert_eq!("0x1p100".parse::<Y>(), Err(Error::Overflow));
+}
+
 /// Spot check addition for `Q12.5`.
 #[test]
 fn test_q12p5_add() {
@@ -31,6 +290,165 @@ fn test_q12p5_add() {
     assert_eq!(x1 + x2, x3)
 }
 
+/// Spot check subtraction and negation for `Q12.5`.
+#[test]
+fn test_q12p5_sub_neg() {
+    define_q_num!(X, Q12.5);
+    let x1 = X::try_from(500.25).unwrap();
+    let x2 = X::try_from(744.75).unwrap();
+    let x3 = X::try_from(-244.5).unwrap();
+    assert_eq!(x1 - x2, x3);
+    assert_eq!(-x3, X::try_from(244.5).unwrap());
+}
+
+/// Spot check multiplication and division for `Q12.5`.
+#[test]
+fn test_q12p5_mul_div() {
+    define_q_num!(X, Q12.5);
+    let x1 = X::try_from(500.25).unwrap();
+    let x2 = X::try_from(-2.5).unwrap();
+    let x3 = X::try_from(-1250.625).unwrap();
+    assert_eq!(x1 * x2, x3);
+    assert_eq!(x3 / x2, x1);
+    assert_eq!(x3 / x1, x2);
+}
+
+/// Multiplication rounds toward negative infinity; division rounds toward
+/// zero.
+#[test]
+fn test_q4p2_mul_div_rounding() {
+    define_q_num!(X, Q4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(1.25) * f(1.25), f(1.5));
+    assert_eq!(f(-1.25) * f(1.25), f(-1.75));
+    assert_eq!(f(1.0) / f(3.0), f(0.25));
+    assert_eq!(f(-1.0) / f(3.0), f(-0.25));
+}
+
+/// Spot check the checked, wrapping, saturating and overflowing methods for
+/// `Q4.2`.
+#[test]
+fn test_q4p2_overflow_methods() {
+    define_q_num!(X, Q4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(7.0).checked_add(f(0.75)), Some(X::MAX));
+    assert_eq!(f(7.0).checked_add(f(1.0)), None);
+    assert_eq!(f(7.0).wrapping_add(f(1.0)), X::MIN);
+    assert_eq!(f(7.0).saturating_add(f(1.0)), X::MAX);
+    assert_eq!(f(-7.0).saturating_sub(f(2.0)), X::MIN);
+    assert_eq!(f(-7.0).overflowing_sub(f(2.0)), (f(7.0), true));
+    assert_eq!(f(3.0).checked_mul(f(2.5)), Some(f(7.5)));
+    assert_eq!(f(3.0).checked_mul(f(3.0)), None);
+    assert_eq!(f(3.0).wrapping_mul(f(3.0)), f(-7.0));
+    assert_eq!(f(3.0).saturating_mul(f(3.0)), X::MAX);
+    assert_eq!(f(-3.0).saturating_mul(f(3.0)), X::MIN);
+    assert_eq!(f(-3.0).overflowing_mul(f(-3.0)), (f(-7.0), true));
+    assert_eq!(f(1.0).checked_div(f(0.0)), None);
+    assert_eq!(f(4.0).checked_div(f(0.25)), None);
+    assert_eq!(f(-4.0).saturating_div(f(0.25)), X::MIN);
+    assert_eq!(X::MIN.overflowing_div(f(-1.0)), (X::MIN, true));
+    assert_eq!(X::MIN.checked_neg(), None);
+    assert_eq!(X::MIN.wrapping_neg(), X::MIN);
+    assert_eq!(X::MIN.saturating_neg(), X::MAX);
+    assert_eq!(f(-2.5).abs(), f(2.5));
+    assert_eq!(X::MIN.checked_abs(), None);
+    assert_eq!(X::MIN.saturating_abs(), X::MAX);
+    assert_eq!(X::MIN.overflowing_abs(), (X::MIN, true));
+}
+
+/// Saturation of unsigned types with padding lands on `MAX`, not on the
+/// largest inner value.
+#[test]
+fn test_uq4p2_overflow_methods() {
+    define_q_num!(X, UQ4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(15.0).saturating_add(f(2.0)), X::MAX);
+    assert_eq!(f(1.0).saturating_sub(f(2.0)), X::MIN);
+    assert_eq!(f(15.0).overflowing_add(f(2.0)), (f(1.0), true));
+    assert_eq!(f(8.0).saturating_mul(f(2.0)), X::MAX);
+    assert_eq!(f(8.0).wrapping_mul(f(2.5)), f(4.0));
+    assert_eq!(f(8.0).checked_div(f(0.25)), None);
+    assert_eq!(f(1.0).wrapping_neg(), f(15.0));
+    assert_eq!(f(0.0).checked_neg(), Some(f(0.0)));
+}
+
+/// Overflow in `*` panics in debug builds.
+#[test]
+#[should_panic(expected = "attempt to multiply with overflow")]
+#[cfg(debug_assertions)]
+fn test_q4p2_mul_overflow() {
+    define_q_num!(X, Q4.2);
+    let x = X::try_from(4.0).unwrap();
+    let _ = x * x;
+}
+
+/// With `overflow = saturate`, the plain operators saturate.
+#[test]
+fn test_overflow_saturate() {
+    define_q_num!(X, Q4.2, overflow = saturate);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(7.0) + f(1.0), X::MAX);
+    assert_eq!(f(-7.0) - f(2.0), X::MIN);
+    assert_eq!(f(-3.0) * f(3.0), X::MIN);
+    assert_eq!(f(4.0) / f(0.25), X::MAX);
+    assert_eq!(-X::MIN, X::MAX);
+    let mut x = f(6.0);
+    x += f(6.0);
+    assert_eq!(x, X::MAX);
+}
+
+/// With `overflow = wrap`, the plain operators wrap, even in debug builds.
+#[test]
+fn test_overflow_wrap() {
+    define_q_num!(X, UQ4.2, overflow = wrap);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(15.0) + f(2.0), f(1.0));
+    assert_eq!(f(1.0) - f(2.0), f(15.0));
+    assert_eq!(f(8.0) * f(2.5), f(4.0));
+}
+
+/// With `overflow = panic`, the plain operators panic, even in release builds.
+#[test]
+#[should_panic(expected = "attempt to add with overflow")]
+fn test_overflow_panic() {
+    define_q_num!(X, Q4.2, overflow = panic);
+    let x = X::try_from(7.0).unwrap();
+    let _ = x + x;
+}
+
+/// Spot check remainder and shifts for `UQ4.2`, which has padding bits.
+#[test]
+fn test_uq4p2_rem_shifts() {
+    define_q_num!(X, UQ4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(7.75) % f(2.5), f(0.25));
+    assert_eq!(f(1.75) << 2, f(7.0));
+    assert_eq!(f(7.0) >> 3, f(0.75));
+    assert_eq!((f(7.0) >> 3).to_bits() & !X::USED_MASK, 0);
+}
+
+/// The compound assignment operators match their binary counterparts.
+#[test]
+fn test_q6p2_assign_ops() {
+    define_q_num!(X, Q6.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    let mut x = f(3.5);
+    x += f(1.25);
+    assert_eq!(x, f(4.75));
+    x -= f(0.75);
+    assert_eq!(x, f(4.0));
+    x *= f(-1.5);
+    assert_eq!(x, f(-6.0));
+    x /= f(4.0);
+    assert_eq!(x, f(-1.5));
+    x %= f(1.0);
+    assert_eq!(x, f(-0.5));
+    x <<= 3;
+    assert_eq!(x, f(-4.0));
+    x >>= 1;
+    assert_eq!(x, f(-2.0));
+}
+
 /// Spot check the macro-generated constants for `Q1.6`.
 #[test]
 fn test_q1p6_constants() {
@@ -83,6 +501,29 @@ fn test_q4p2_hygienic_inner_type() {
     assert_eq!(std::mem::size_of::<X>(), 1);
 }
 
+#[deny(private_interfaces)]
+mod outer {
+    pub mod inner {
+        q_num::define_q_num!(pub(super) X, Q6.2);
+        q_num::define_q_num!(Y, Q6.2);
+
+        pub fn y_bits() -> i8 {
+            Y::try_from(1.5).unwrap().to_bits()
+        }
+    }
+
+    pub fn x_value() -> f64 {
+        f64::from(inner::X::try_from(-1.5).unwrap() * inner::X::try_from(10.0).unwrap())
+    }
+}
+
+/// The visibility modifier applies to the struct, its constants and methods.
+#[test]
+fn test_visibility() {
+    assert_eq!(outer::x_value(), -15.0);
+    assert_eq!(outer::inner::y_bits(), 6);
+}
+
 /// Exhaustively test all valid inner values for `UQ5.3`.
 #[test]
 fn test_q5p3_inner_values() {
@@ -109,6 +550,315 @@ fn test_q4p2_inner_values() {
     }
 }
 
+/// Spot check `from_f64_round` for `Q6.2`, where the resolution is 0.25.
+#[test]
+fn test_q6p2_from_f64_round() {
+    use q_num::RoundingMode::*;
+    define_q_num!(X, Q6.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    let round = |v: f64, mode| X::from_f64_round(v, mode).unwrap();
+    assert_eq!(round(0.3, NearestTiesEven), f(0.25));
+    assert_eq!(round(-0.3, NearestTiesEven), f(-0.25));
+    assert_eq!(round(0.375, NearestTiesEven), f(0.5));
+    assert_eq!(round(0.625, NearestTiesEven), f(0.5));
+    assert_eq!(round(0.625, NearestTiesAway), f(0.75));
+    assert_eq!(round(-0.625, NearestTiesAway), f(-0.75));
+    assert_eq!(round(-0.3, Floor), f(-0.5));
+    assert_eq!(round(0.3, Ceil), f(0.5));
+    assert_eq!(round(-0.3, TowardZero), f(-0.25));
+    assert_eq!(round(31.8, NearestTiesEven), X::MAX);
+    assert!(X::from_f64_round(31.9, NearestTiesEven).is_err());
+    assert!(X::from_f64_round(f64::NAN, Floor).is_err());
+}
+
+/// The fallible conversions return `Error` variants that can be matched on.
+#[test]
+fn test_errors() {
+    define_q_num!(X, Q6.2);
+    define_q_num!(Y, Q2.6);
+    let out_of_range = Error::OutOfRange {
+        value: 32.0,
+        min: -32.0,
+        max: 31.75,
+    };
+    assert_eq!(X::try_from(32.0), Err(out_of_range));
+    assert_eq!(
+        out_of_range.to_string(),
+        "32 is out of range (-32 to 31.75)"
+    );
+    assert_eq!(X::try_from(f64::NAN), Err(Error::NotANumber));
+    assert_eq!(
+        X::try_from(f32::INFINITY).unwrap_err().to_string(),
+        "inf is out of range (-32 to 31.75)"
+    );
+    assert_eq!(X::try_from(32i32), Err(Error::Overflow));
+    assert_eq!(X::MAX.try_convert::<Y>(), Err(Error::Overflow));
+    let error: Box<dyn std::error::Error> = Box::new(Error::Overflow);
+    assert_eq!(error.to_string(), "value is out of range");
+}
+
+/// The `rounding` option sets the mode used by `TryFrom<f64>`.
+#[test]
+fn test_rounding_option() {
+    define_q_num!(X, Q6.2);
+    define_q_num!(Y, Q6.2, rounding = nearest_even);
+    define_q_num!(Z, Q6.2, rounding = floor);
+    assert_eq!(X::ROUNDING, q_num::RoundingMode::TowardZero);
+    assert_eq!(Y::ROUNDING, q_num::RoundingMode::NearestTiesEven);
+    assert_eq!(f64::from(X::try_from(-0.4).unwrap()), -0.25);
+    assert_eq!(f64::from(Y::try_from(-0.4).unwrap()), -0.5);
+    assert_eq!(f64::from(Z::try_from(0.4).unwrap()), 0.25);
+    assert_eq!(f64::from(Z::try_from(-0.1).unwrap()), -0.25);
+}
+
+/// Spot check the saturating, wrapping and lossy float conversions for `Q4.2`.
+#[test]
+fn test_q4p2_from_f64_saturating_wrapping_lossy() {
+    define_q_num!(X, Q4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(X::from_f64_saturating(7.9), X::MAX);
+    assert_eq!(X::from_f64_saturating(-8.1), X::MIN);
+    assert_eq!(X::from_f64_saturating(f64::INFINITY), X::MAX);
+    assert_eq!(X::from_f64_saturating(-2.6), f(-2.5));
+    assert_eq!(X::from_f64_wrapping(8.0), X::MIN);
+    assert_eq!(X::from_f64_wrapping(10.0), f(-6.0));
+    assert_eq!(X::from_f64_wrapping(-9.5), f(6.5));
+    assert_eq!(X::from_f64_wrapping(1e300), f(0.0));
+    assert_eq!(X::from_f64_lossy(f64::NAN), f(0.0));
+    assert_eq!(X::from_f64_lossy(f64::NEG_INFINITY), X::MIN);
+    assert_eq!(X::from_f64_lossy(1e300), X::MAX);
+    assert_eq!(X::from_f64_lossy(1.3), f(1.25));
+}
+
+/// Spot check the wrapping float conversion for `UQ4.2`.
+#[test]
+fn test_uq4p2_from_f64_wrapping() {
+    define_q_num!(X, UQ4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(X::from_f64_wrapping(17.5), f(1.5));
+    assert_eq!(X::from_f64_wrapping(-0.25), X::MAX);
+    assert_eq!(X::from_f64_saturating(-0.25), X::MIN);
+}
+
+/// `from_f64_saturating` panics on NaN, which has no nearest bound.
+#[test]
+#[should_panic(expected = "NaN cannot be converted to Q4.2")]
+fn test_q4p2_from_f64_saturating_nan() {
+    define_q_num!(X, Q4.2);
+    let _ = X::from_f64_saturating(f64::NAN);
+}
+
+/// `from_f64_wrapping` panics on an infinity, which cannot wrap.
+#[test]
+#[should_panic(expected = "inf cannot be converted to Q4.2")]
+fn test_q4p2_from_f64_wrapping_infinite() {
+    define_q_num!(X, Q4.2);
+    let _ = X::from_f64_wrapping(f64::INFINITY);
+}
+
+/// Spot check the `f32` conversions for `Q12.5`, which converts losslessly.
+#[test]
+fn test_q12p5_f32() {
+    define_q_num!(X, Q12.5);
+    let x = X::try_from(-244.53125f32).unwrap();
+    assert_eq!(x, X::try_from(-244.53125).unwrap());
+    assert_eq!(f32::from(x), -244.53125);
+    assert_eq!(x.to_f32(), -244.53125);
+    assert!(X::try_from(2048.0f32).is_err());
+}
+
+/// `to_f32` rounds to the nearest `f32` when `USED_BITS > 24`.
+#[test]
+fn test_q8p24_to_f32() {
+    define_q_num!(X, Q8.24);
+    let x = X::from_bits(0x0100_0001);
+    assert_eq!(f64::from(x), 1.0 + 2f64.powi(-24));
+    assert_eq!(x.to_f32(), 1.0);
+}
+
+/// Spot check the integer conversions for `Q4.2`.
+#[test]
+fn test_q4p2_int_conversions() {
+    define_q_num!(X, Q4.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(X::try_from(7i32).unwrap(), f(7.0));
+    assert_eq!(X::try_from(-8i64).unwrap(), f(-8.0));
+    assert_eq!(X::try_from(5usize).unwrap(), f(5.0));
+    assert!(X::try_from(8i8).is_err());
+    assert!(X::try_from(-9i32).is_err());
+    assert!(X::try_from(u64::MAX).is_err());
+}
+
+/// Small integer types convert via `From` when every value fits.
+#[test]
+fn test_q12p5_int_from() {
+    define_q_num!(X, Q12.5);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(X::from(-128i8), f(-128.0));
+    assert_eq!(X::from(255u8), f(255.0));
+    assert_eq!(X::try_from(2047i16).unwrap(), f(2047.0));
+    assert!(X::try_from(2048u16).is_err());
+    define_q_num!(Y, UQ8.8);
+    assert_eq!(Y::from(255u8), Y::try_from(255.0).unwrap());
+    assert!(Y::try_from(-1i8).is_err());
+}
+
+/// Spot check the integer-part methods for `Q6.2`.
+#[test]
+fn test_q6p2_int_part() {
+    define_q_num!(X, Q6.2);
+    let f = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(f(2.75).to_int_trunc(), 2);
+    assert_eq!(f(-2.75).to_int_trunc(), -2);
+    assert_eq!(f(-3.0).to_int_trunc(), -3);
+    assert_eq!(f(2.75).to_int_floor(), 2);
+    assert_eq!(f(-2.25).to_int_floor(), -3);
+    assert_eq!(f(2.5).to_int_round(), 3);
+    assert_eq!(f(-2.5).to_int_round(), -3);
+    assert_eq!(f(-2.25).to_int_round(), -2);
+    assert_eq!(X::MAX.to_int_round(), 32);
+    assert_eq!(X::MIN.to_int_round(), -32);
+    assert_eq!(f(-2.25).int_part(), f(-3.0));
+    assert_eq!(f(-2.25).frac_part(), f(0.75));
+    assert_eq!(f(2.25).int_part(), f(2.0));
+    assert_eq!(f(2.25).frac_part(), f(0.25));
+}
+
+/// The integer-part methods of unsigned and fraction-free types.
+#[test]
+fn test_int_part_unsigned_and_integer_only() {
+    define_q_num!(X, UQ4.2);
+    let x = X::try_from(13.5).unwrap();
+    assert_eq!(x.to_int_trunc(), 13);
+    assert_eq!(x.to_int_round(), 14);
+    assert_eq!(X::MAX.to_int_round(), 16);
+    assert_eq!(x.frac_part(), X::try_from(0.5).unwrap());
+    define_q_num!(Y, Q8.0);
+    let y = Y::try_from(-100.0).unwrap();
+    assert_eq!(y.to_int_trunc(), -100);
+    assert_eq!(y.to_int_round(), -100);
+    assert_eq!(y.int_part(), y);
+    assert_eq!(y.frac_part(), Y::from(0i8));
+}
+
+/// Lossless conversions between Q formats.
+#[test]
+fn test_convert_lossless() {
+    define_q_num!(Sample, Q1.15);
+    define_q_num!(Acc, Q8.24);
+    define_q_num!(U, UQ4.2);
+    define_q_num!(S, Q5.2);
+    let a = Sample::try_from(-0.999969482421875).unwrap();
+    let b: Acc = a.convert();
+    assert_eq!(f64::from(b), -0.999969482421875);
+    assert_eq!(Sample::MIN.convert::<Acc>(), Acc::try_from(-1.0).unwrap());
+    let c: S = U::MAX.convert();
+    assert_eq!(f64::from(c), 15.75);
+}
+
+/// Narrowing conversions between Q formats round and handle overflow as
+/// requested.
+#[test]
+fn test_convert_narrowing() {
+    define_q_num!(Sample, Q1.15);
+    define_q_num!(Acc, Q8.24);
+    define_q_num!(Coarse, Q4.2, rounding = nearest_even);
+    let acc = |v: f64| Acc::try_from(v).unwrap();
+    let sample = |v: f64| Sample::try_from(v).unwrap();
+    let coarse = |v: f64| Coarse::try_from(v).unwrap();
+    let x = acc(0.5 + 2f64.powi(-16));
+    let modes = [
+        OverflowMode::Panic,
+        OverflowMode::Wrap,
+        OverflowMode::Saturate,
+    ];
+    for overflow in modes {
+        let convert = |mode| x.convert_with::<Sample>(mode, overflow);
+        assert_eq!(convert(RoundingMode::NearestTiesEven), sample(0.5));
+        assert_eq!(
+            convert(RoundingMode::NearestTiesAway),
+            sample(0.5 + 2f64.powi(-15))
+        );
+        assert_eq!(convert(RoundingMode::Ceil), sample(0.5 + 2f64.powi(-15)));
+        assert_eq!(convert(RoundingMode::Floor), sample(0.5));
+    }
+    let y = acc(-1.5);
+    let convert = |overflow| y.convert_with::<Sample>(RoundingMode::Floor, overflow);
+    assert_eq!(convert(OverflowMode::Saturate), Sample::MIN);
+    assert_eq!(convert(OverflowMode::Wrap), sample(0.5));
+    assert_eq!(acc(-0.375).try_convert::<Coarse>().unwrap(), coarse(-0.5));
+    assert_eq!(acc(0.375).try_convert::<Coarse>().unwrap(), coarse(0.5));
+    assert!(acc(8.0).try_convert::<Coarse>().is_err());
+    assert!(acc(7.9).try_convert::<Coarse>().is_err());
+    assert_eq!(acc(7.8).try_convert::<Coarse>().unwrap(), Coarse::MAX);
+}
+
+/// Conversions between signed and unsigned Q formats.
+#[test]
+fn test_convert_signedness() {
+    define_q_num!(U, UQ8.8);
+    define_q_num!(S, Q8.8);
+    let s = S::try_from(-2.5).unwrap();
+    assert!(s.try_convert::<U>().is_err());
+    assert_eq!(
+        s.convert_with::<U>(RoundingMode::Floor, OverflowMode::Saturate),
+        U::MIN
+    );
+    assert_eq!(
+        f64::from(s.convert_with::<U>(RoundingMode::Floor, OverflowMode::Wrap)),
+        253.5
+    );
+    let u = U::try_from(200.25).unwrap();
+    assert_eq!(
+        u.convert_with::<S>(RoundingMode::Floor, OverflowMode::Saturate),
+        S::MAX
+    );
+    assert_eq!(
+        f64::from(u.convert_with::<S>(RoundingMode::Floor, OverflowMode::Wrap)),
+        -55.75
+    );
+}
+
+/// `OverflowMode::Panic` panics when the converted value is out of range.
+#[test]
+#[should_panic(expected = "value is out of range for Q1.15")]
+fn test_convert_panic() {
+    define_q_num!(Sample, Q1.15);
+    define_q_num!(Acc, Q8.24);
+    let acc = Acc::try_from(1.0).unwrap();
+    let _: Sample = acc.convert_with(RoundingMode::Floor, OverflowMode::Panic);
+}
+
+/// The product format of `Q6.2 * Q4.4` is `Q10.6`, which holds the exact
+/// result of `widening_mul`.
+#[test]
+fn test_widening_mul() {
+    define_q_num!(A, Q6.2);
+    define_q_num!(B, Q4.4);
+    define_q_num!(P, Q6.2 * Q4.4);
+    assert_eq!(P::Q_NOTATION, "Q10.6");
+    assert_eq!(P::TOTAL_BITS, 16);
+    let a = A::try_from(-31.75).unwrap();
+    let b = B::try_from(7.9375).unwrap();
+    let p: P = a.widening_mul(b);
+    assert_eq!(f64::from(p), -31.75 * 7.9375);
+    let p: P = A::MIN.widening_mul(B::MIN);
+    assert_eq!(f64::from(p), 256.0);
+    define_q_num!(Acc, Q16.16);
+    let acc: Acc = a.widening_mul(b);
+    assert_eq!(f64::from(acc), -31.75 * 7.9375);
+}
+
+/// `widening_mul` with unsigned operands and a 64-bit product format.
+#[test]
+fn test_widening_mul_unsigned() {
+    define_q_num!(A, UQ16.16);
+    define_q_num!(P, UQ16.16 * UQ16.16);
+    assert_eq!(P::TOTAL_BITS, 64);
+    let p: P = A::MAX.widening_mul(A::MAX);
+    assert_eq!(p.to_bits(), 0xFFFF_FFFE_0000_0001);
+}
+
 /// Spot check an out-of-range input for `Q4.2`.
 #[test]
 #[should_panic]
@@ -116,3 +866,346 @@ fn test_q4p2_out_of_range() {
     define_q_num!(Q, Q4.2);
     let _ = Q::try_from(16.00).unwrap();
 }
+
+/// Values sort and hash by their numeric value.
+#[test]
+fn test_ord_and_hash() {
+    use std::collections::{BTreeSet, HashSet};
+    define_q_num!(X, Q4.2);
+    define_q_num!(U, UQ4.2);
+    let x = |v: f64| X::try_from(v).unwrap();
+    let mut values = [x(1.5), X::MAX, x(-0.25), X::MIN, x(0.0), x(-3.75)];
+    values.sort();
+    assert_eq!(values, [X::MIN, x(-3.75), x(-0.25), x(0.0), x(1.5), X::MAX]);
+    assert!(x(-0.25) < x(0.25));
+    assert_eq!(values.iter().max(), Some(&X::MAX));
+    assert!(U::try_from(8.0).unwrap() > U::try_from(7.75).unwrap());
+    assert!(U::MIN < U::MAX);
+    let set: BTreeSet<_> = values.iter().copied().collect();
+    assert_eq!(set.first(), Some(&X::MIN));
+    let set: HashSet<_> = values.iter().copied().collect();
+    assert!(set.contains(&x(-3.75)));
+}
+
+mod attributes {
+    use q_num::define_q_num;
+
+    define_q_num!(
+        /// A sample.
+        #[derive(Default)]
+        #[repr(transparent)]
+        pub Sample, Q1.15
+    );
+
+    define_q_num!(
+        #[cfg(any())]
+        Missing,
+        Q4.4
+    );
+    define_q_num!(#[cfg(all())] pub Present, UQ4.4);
+
+    // Would conflict with `Missing` above if its impls were not configured out.
+    struct Missing;
+
+    #[test]
+    fn test_attributes() {
+        assert_eq!(Sample::default(), Sample::from_bits(0));
+        assert_eq!(std::mem::size_of::<Sample>(), 2);
+        let mut set = std::collections::HashSet::new();
+        set.insert(Sample::MAX);
+        assert!(set.contains(&Sample::MAX));
+        assert_eq!(Present::MAX.to_bits(), 0xff);
+        let _ = Missing;
+    }
+}
+
+mod attribute_form {
+    use q_num::q_num;
+
+    /// An angle, in degrees.
+    #[q_num(Q10.6, overflow = wrap)]
+    #[derive(Default)]
+    pub struct Angle;
+
+    #[q_num(UQ1.15 * UQ1.15)]
+    pub(crate) struct Product;
+
+    /// The declared field must be the generated inner type.
+    #[q_num(Q6.2)]
+    pub struct Offset(i8);
+
+    #[test]
+    fn test_attribute_form() {
+        let a = Angle::try_from(270.5).unwrap();
+        assert_eq!(Angle::default(), Angle::from_bits(0));
+        assert_eq!(Angle::Q_NOTATION, "Q10.6");
+        assert_eq!(f64::from(a + a), 541.0 - 1024.0);
+        assert_eq!(Product::Q_NOTATION, "UQ2.30");
+        assert_eq!(Offset::try_from(-1.5).unwrap().to_bits(), -6);
+    }
+}
+
+mod multiple {
+    use q_num::define_q_num;
+
+    define_q_num! {
+        pub Sample: Q1.15;
+        /// A filter coefficient.
+        pub Coeff: Q2.30, overflow = saturate;
+        Acc: Q16.48;
+        #[cfg(any())]
+        Missing: Q32.0;
+        /// Stored in limbs, so without conversions to the other types.
+        Wide: Q64.128;
+    }
+
+    #[test]
+    fn test_multiple() {
+        assert_eq!(Sample::Q_NOTATION, "Q1.15");
+        assert_eq!(Coeff::Q_NOTATION, "Q2.30");
+        assert_eq!(Acc::Q_NOTATION, "Q16.48");
+        let a = Sample::try_from(-0.75).unwrap();
+        let b = Coeff::from(a);
+        assert_eq!(f64::from(b), -0.75);
+        assert_eq!(f64::from(Acc::from(b) + Acc::from(a)), -1.5);
+        assert_eq!(b + b, Coeff::try_from(-1.5).unwrap());
+        assert_eq!(Wide::try_from(-0.75).unwrap().to_string(), "-0.75");
+    }
+}
+
+/// `Display` prints the exact decimal value, honoring the precision.
+#[test]
+fn test_display() {
+    define_q_num!(X, Q6.2);
+    define_q_num!(U, UQ4.4);
+    define_q_num!(W, Q40.24);
+    let x = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(x(-30.25).to_string(), "-30.25");
+    assert_eq!(x(5.0).to_string(), "5");
+    assert_eq!(X::MIN.to_string(), "-32");
+    assert_eq!(U::MAX.to_string(), "15.9375");
+    assert_eq!(U::from_bits(1).to_string(), "0.0625");
+    assert_eq!(format!("{:.1}", x(0.25)), "0.2");
+    assert_eq!(format!("{:.1}", x(0.75)), "0.8");
+    assert_eq!(format!("{:.0}", x(-2.5)), "-2");
+    assert_eq!(format!("{:.0}", x(31.75)), "32");
+    assert_eq!(format!("{:.3}", x(1.5)), "1.500");
+    assert_eq!(format!("{:.1}", U::from_bits(0xff)), "15.9");
+    assert_eq!(format!("{:8.1}|", x(-1.25)), "    -1.2|");
+    assert_eq!(format!("{:<7}|", x(1.5)), "1.5    |");
+    assert_eq!(format!("{:*^7}", x(1.5)), "**1.5**");
+    assert_eq!(format!("{:+}", x(1.5)), "+1.5");
+    assert_eq!(format!("{:07.2}", x(-1.5)), "-001.50");
+    // Exact, even beyond the precision of `f64`.
+    assert_eq!(W::MAX.to_string(), "549755813887.999999940395355224609375");
+    assert_eq!(W::MIN.to_string(), "-549755813888");
+}
+
+/// `LowerExp` prints the exact value in scientific notation, and `Debug`
+/// adds the format.
+#[test]
+fn test_lower_exp_and_debug() {
+    define_q_num!(X, Q6.2);
+    define_q_num!(U, UQ4.12);
+    let x = |v: f64| X::try_from(v).unwrap();
+    assert_eq!(format!("{:e}", x(-30.25)), "-3.025e1");
+    assert_eq!(format!("{:e}", x(0.0)), "0e0");
+    assert_eq!(format!("{:e}", x(20.0)), "2e1");
+    assert_eq!(format!("{:e}", x(0.25)), "2.5e-1");
+    assert_eq!(format!("{:.1e}", x(0.25)), "2.5e-1");
+    assert_eq!(format!("{:.0e}", x(0.25)), "2e-1");
+    assert_eq!(format!("{:.2e}", x(0.0)), "0.00e0");
+    assert_eq!(format!("{:e}", U::from_bits(1)), "2.44140625e-4");
+    assert_eq!(format!("{:.1e}", U::MAX), "1.6e1");
+    assert_eq!(format!("{:>9e}", x(1.5)), "    1.5e0");
+    for v in [-31.75, -0.25, 0.5, 3.75, 31.0] {
+        assert_eq!(format!("{:e}", x(v)), format!("{v:e}"));
+        assert_eq!(format!("{:.1e}", x(v)), format!("{v:.1e}"));
+    }
+    assert_eq!(format!("{:?}", x(-1.25)), "X(-1.25, Q6.2)");
+    assert_eq!(format!("{:.1?}", U::from_bits(0x1800)), "U(1.5, UQ4.12)");
+}
+
+/// `FromStr` parses every supported syntax with a single exact rounding.
+#[test]
+fn test_from_str() {
+    define_q_num!(X, Q6.2);
+    define_q_num!(C, Q1.15, rounding = nearest_even);
+    define_q_num!(W, Q8.56, rounding = nearest_even);
+    define_q_num!(U, UQ4.4);
+    let x = |v: f64| X::try_from(v).unwrap();
+    assert_eq!("-30.25".parse(), Ok(x(-30.25)));
+    assert_eq!("0x1.8p3".parse(), Ok(x(12.0)));
+    assert_eq!("-0b1.01".parse(), Ok(x(-1.25)));
+    assert_eq!("3125e-4".parse(), Ok(x(0.25)));
+    // Rounds toward zero, the default.
+    assert_eq!("-0.3".parse(), Ok(x(-0.25)));
+    assert_eq!("0.70710678".parse(), Ok(C::from_bits(0x5a82)));
+    assert_eq!("32".parse::<X>(), Err(Error::Overflow));
+    assert_eq!("-32".parse(), Ok(X::MIN));
+    assert_eq!("31.99".parse(), Ok(X::MAX));
+    assert_eq!("-0.5".parse::<U>(), Err(Error::Overflow));
+    assert_eq!("-0.01".parse(), Ok(U::from_bits(0)));
+    assert_eq!("1,5".parse::<X>(), Err(Error::InvalidSyntax));
+    assert_eq!(Error::InvalidSyntax.to_string(), "invalid number syntax");
+    // Exact for formats wider than the 53-bit significand of `f64`.
+    let w: W = "0.1".parse().unwrap();
+    assert_eq!(w.to_bits(), 0x0019_9999_9999_999a);
+    assert_eq!(W::MAX.to_string().parse(), Ok(W::MAX));
+    assert_eq!(W::MIN.to_string().parse(), Ok(W::MIN));
+}
+
+/// The binary, octal and hex forms show the inner value, and `{:#b}` shows
+/// the layout.
+#[test]
+fn test_binary_and_hex() {
+    define_q_num!(U, UQ4.2);
+    define_q_num!(X, Q4.2);
+    define_q_num!(S, Q1.15);
+    let u = U::try_from(11.25).unwrap();
+    assert_eq!(format!("{u:b}"), "10110100");
+    assert_eq!(format!("{u:#b}"), "1011.01__");
+    assert_eq!(format!("{u:#12b}|"), "   1011.01__|");
+    assert_eq!(format!("{u:<#10b}|"), "1011.01__ |");
+    assert_eq!(format!("{u:x} {u:X} {u:#x} {u:o}"), "b4 B4 0xb4 264");
+    let x = X::try_from(-4.75).unwrap();
+    assert_eq!(format!("{x:b}"), "10110100");
+    assert_eq!(format!("{x:#b}"), "1|011.01__");
+    assert_eq!(format!("{x:08x}"), "000000b4");
+    assert_eq!(format!("{:#b}", S::MIN), "1|.000000000000000");
+    assert_eq!(format!("{:04X}", S::MAX), "7FFF");
+}
+
+/// With `layout = right`, the value sits in the low bits and the padding above it
+/// is sign-extended.
+#[test]
+fn test_right_layout() {
+    define_q_num!(X, Q4.2, layout = right);
+    define_q_num!(U, UQ4.2, layout = right);
+    assert_eq!((X::USED_MASK, X::CONVERSION_FACTOR), (0x3F, 4.0));
+    assert_eq!((X::MIN.to_bits(), X::MAX.to_bits()), (-32, 31));
+    assert_eq!((U::MIN.to_bits(), U::MAX.to_bits()), (0, 0x3F));
+    let x = X::try_from(-4.75).unwrap();
+    assert_eq!(x.to_bits(), -19);
+    assert_eq!(format!("{x:b} {x:#b}"), "11101101 __1|011.01");
+    assert_eq!(format!("{x} {x:?}"), "-4.75 X(-4.75, Q4.2)");
+    assert_eq!(f64::from(x), -4.75);
+    // The padding is ignored, and sign-extended for signed types.
+    assert_eq!(X::from_bits(0b0010_1101), x);
+    assert_eq!(U::from_bits(0b1110_1101).to_bits(), 0b10_1101);
+    assert_eq!(f64::from(U::from_bits(0b1110_1101)), 11.25);
+    assert!(X::MIN < x && x < X::try_from(0.25).unwrap());
+    assert_eq!(
+        (x.to_int_floor(), x.to_int_trunc(), x.to_int_round()),
+        (-5, -4, -5)
+    );
+    assert_eq!(f64::from(x.frac_part()), 0.25);
+    assert_eq!(f64::from(X::try_from(3).unwrap()), 3.0);
+}
+
+/// Arithmetic with `layout = right` matches the left layout, including overflow
+/// handling and 128-bit rescaling.
+#[test]
+fn test_right_layout_arithmetic() {
+    define_q_num!(X, Q4.2, layout = right);
+    define_q_num!(W, Q32.64, layout = right);
+    let x = |value: f64| X::try_from(value).unwrap();
+    assert_eq!(x(2.5) + x(-1.25), x(1.25));
+    assert_eq!(x(2.5) - x(3.0), x(-0.5));
+    assert_eq!(x(2.5) * x(-1.5), x(-3.75));
+    assert_eq!(x(3.0) / x(-4.0), x(-0.75));
+    assert_eq!(x(7.0) % x(2.5), x(2.0));
+    assert_eq!(-x(4.75), x(-4.75));
+    assert_eq!(x(7.75).wrapping_add(x(0.25)), X::MIN);
+    assert_eq!(x(7.75).checked_add(x(0.25)), None);
+    assert_eq!(x(7.75).saturating_mul(x(7.75)), X::MAX);
+    assert_eq!(X::MIN.saturating_neg(), X::MAX);
+    assert_eq!(X::MIN.wrapping_abs().to_bits(), -32);
+    assert_eq!((x(-4.75) >> 1).to_bits(), -10);
+    assert_eq!(x(3.0) << 2, x(-4.0));
+    let w = W::try_from(1.5).unwrap() * W::try_from(-2.25).unwrap();
+    assert_eq!(w.to_bits(), -27 << 61);
+    assert_eq!(f64::from(w / W::try_from(1.5).unwrap()), -2.25);
+}
+
+/// Converting between the left and right layouts, via the bit accessors and
+/// `From`.
+#[test]
+fn test_layout_conversions() {
+    define_q_num! {
+        L: Q4.2;
+        R: Q4.2, layout = right;
+    }
+    let l = L::try_from(-4.75).unwrap();
+    let r = R::try_from(-4.75).unwrap();
+    assert_eq!((l.to_left_bits(), l.to_right_bits()), (-76, -19));
+    assert_eq!((r.to_left_bits(), r.to_right_bits()), (-76, -19));
+    assert_eq!(R::from_left_bits(l.to_bits()), r);
+    assert_eq!(L::from_right_bits(r.to_bits()), l);
+    assert_eq!(L::from_left_bits(-73), l);
+    assert_eq!(R::from(l), r);
+    assert_eq!(L::from(r), l);
+}
+
+/// The `storage` option widens the inner type; the padding follows the layout.
+#[test]
+fn test_storage_override() {
+    define_q_num!(X, Q4.2, storage = i32);
+    define_q_num!(R, UQ4.2, storage = u32, layout = right);
+    define_q_num!(W, Q4.2, storage = i128);
+    assert_eq!(core::mem::size_of::<X>(), 4);
+    assert_eq!((X::TOTAL_BITS, X::USED_BITS, X::PAD_BITS), (32, 6, 26));
+    assert_eq!(X::USED_MASK as u32, 0xFC00_0000);
+    let x = |value: f64| X::try_from(value).unwrap();
+    assert_eq!(x(-4.75).to_bits(), -19 << 26);
+    assert_eq!(x(2.5) * x(-1.5), x(-3.75));
+    assert_eq!(x(3.0) / x(-4.0), x(-0.75));
+    assert_eq!(x(7.75).wrapping_add(x(0.25)), X::MIN);
+    assert_eq!(x(-4.75).to_string(), "-4.75");
+    assert_eq!((R::USED_MASK, R::MAX.to_bits()), (0x3F, 0x3F));
+    assert_eq!(R::from_bits(0xFFFF_FF2D).to_bits(), 0x2D);
+    assert_eq!(f64::from(R::from_bits(0x2D)), 11.25);
+    let w = |value: f64| W::try_from(value).unwrap();
+    assert_eq!(W::PAD_BITS, 122);
+    assert_eq!(w(2.5) * w(-1.5), w(-3.75));
+    assert_eq!(w(3.0) / w(-4.0), w(-0.75));
+    assert_eq!(w(7.75).overflowing_mul(w(2.0)), (w(-0.5), true));
+}
+
+/// The bit accessors, `from_f64_round` and `q!` work in `const` items.
+#[test]
+fn test_const() {
+    use q_num::q;
+    define_q_num!(Coeff, Q1.15, rounding = nearest_even);
+    define_q_num!(U, UQ4.4);
+    const TABLE: [Coeff; 4] = [
+        q!(Coeff, 0.7071),
+        q!(Coeff, -0.5),
+        q!(Coeff, -1),
+        q!(Coeff, "0x1.8p-1"),
+    ];
+    const BITS: i16 = TABLE[0].to_bits();
+    static HALF: Coeff = Coeff::from_bits(0x4000);
+    const THIRD: Coeff = match Coeff::from_f64_round(1.0 / 3.0, RoundingMode::Floor) {
+        Ok(value) => value,
+        Err(_) => panic!(),
+    };
+    assert_eq!(BITS, 0x5a82);
+    assert_eq!(TABLE[1], -HALF);
+    assert_eq!(TABLE[2], Coeff::MIN);
+    assert_eq!(TABLE[3].to_string(), "0.75");
+    assert_eq!(THIRD.to_bits(), 0x2aaa);
+    assert_eq!(q!(U, 15.9375), U::MAX);
+    assert_eq!(Coeff::from_str_const("1"), Err(Error::Overflow));
+}
+
+/// `q!` can name `Self` inside an `impl`.
+#[test]
+fn test_const_self() {
+    use q_num::q;
+    define_q_num!(X, Q4.4);
+    impl X {
+        const HALF: X = q!(Self, 0.5);
+    }
+    assert_eq!(X::HALF.to_bits(), 0x08);
+}